@@ -1,148 +1,145 @@
-use std::collections::VecDeque;
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, VecDeque};
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, PartialEq, Clone)]
+use crate::stream::Csr;
+
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum Directionality {
     Directed,
     Undirected
 }
 
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct Traversal {
+    pub order: Vec<usize>,
+    pub tree_edges: Vec<(usize, usize)>,
+}
+
+impl Traversal {
+    pub fn print(&self, tree: bool) {
+        if tree {
+            println!("{:?}", self.tree_edges)
+        }
+        for node in &self.order {
+            print!("{node} ");
+        }
+        println!();
+    }
+}
+
+//for every node a list of (neighbour, edge index)
+type Incidence = Vec<Vec<(usize, usize)>>;
+
 #[derive(Debug)]
 pub struct Graph{
     pub directionality: Directionality,
     pub node_quantity: usize,
-    adj: Vec<Vec<usize>>
+    //u32 targets and one offset per node, the streaming loader fills it in place
+    adj: Csr
 }
 
 impl Graph {
     pub fn new(directionality: Directionality, node_quantity: usize, edges: Vec<(usize, usize)>) -> Self {
-        let mut adj: Vec<Vec<usize>> = Vec::new();
-        for _ in 1..=node_quantity {
-            adj.push(Vec::new());
-        }
-        match directionality {
-            Directionality::Directed => {
-                for edge in edges {
-                    adj[edge.0 - 1].push(edge.1);
-                }
-            },
-            Directionality::Undirected => {
-                for edge in edges {
-                    adj[edge.0 - 1].push(edge.1);
-                    adj[edge.1 - 1].push(edge.0);
-                }
-            }
-        }
+        let adj = Csr::from_edges(directionality.clone(), node_quantity, &edges);
         Self { directionality: directionality, node_quantity: node_quantity, adj: adj }
     }
 
+    //adjacency built elsewhere, the streaming loader
+    pub fn from_csr(adj: Csr) -> Self {
+        Self { directionality: adj.directionality.clone(), node_quantity: adj.node_quantity(), adj }
+    }
+
+    //neighbours of node in insertion order, both numbered from 1
+    fn neighbours(&self, node: usize) -> impl Iterator<Item = usize> + '_ {
+        self.adj.neighbours(node).iter().map(|to| *to as usize)
+    }
+
     pub fn dfs(&self, tree: bool){
-        let mut visited: Vec<usize> = Vec::new();
-        let mut traversal: Vec<usize> = Vec::new();
-        if tree == false {
-            for i in 1..=self.node_quantity {
-                if !visited.contains(&i) {
-                    let mut stack: Vec<usize> = Vec::new();
-                    visited.push(i);
-                    stack.push(i);
-                    while !stack.is_empty() {
-                        let node = stack.pop().unwrap();
-                        traversal.push(node);
-                        for j in &self.adj[node-1] {
-                            if !visited.contains(j) {
-                                visited.push(j.clone());
-                                stack.push(j.clone());
-                            }
+        self.dfs_traversal().print(tree);
+    }
+
+    pub fn bfs(&self, tree: bool){
+        self.bfs_traversal().print(tree);
+    }
+
+    //visit order and the spanning forest of the stack based search
+    pub fn dfs_traversal(&self) -> Traversal {
+        let mut visited: Vec<bool> = vec![false; self.node_quantity];
+        let mut traversal = Traversal { order: Vec::new(), tree_edges: Vec::new() };
+        for i in 1..=self.node_quantity {
+            if !visited[i - 1] {
+                let mut stack: Vec<usize> = Vec::new();
+                visited[i - 1] = true;
+                stack.push(i);
+                while let Some(node) = stack.pop() {
+                    traversal.order.push(node);
+                    for j in self.neighbours(node) {
+                        if !visited[j - 1] {
+                            traversal.tree_edges.push((node, j));
+                            visited[j - 1] = true;
+                            stack.push(j);
                         }
                     }
                 }
             }
-        } else {
-            let mut tree_edges: Vec<(usize, usize)> = Vec::new();
-            for i in 1..=self.node_quantity {
-                if !visited.contains(&i) {
-                    let mut stack: Vec<usize> = Vec::new();
-                    visited.push(i);
-                    stack.push(i);
-                    while !stack.is_empty() {
-                        let node = stack.pop().unwrap();
-                        traversal.push(node);
-                        for j in &self.adj[node-1] {
-                            if !visited.contains(j) {
-                                tree_edges.push((node, j.clone()));
-                                visited.push(j.clone());
-                                stack.push(j.clone());
-                            }
+        }
+        traversal
+    }
+
+    //visit order and the spanning forest of the queue based search
+    pub fn bfs_traversal(&self) -> Traversal {
+        let mut visited: Vec<bool> = vec![false; self.node_quantity];
+        let mut traversal = Traversal { order: Vec::new(), tree_edges: Vec::new() };
+        for i in 1..=self.node_quantity {
+            if !visited[i - 1] {
+                let mut queue: VecDeque<usize> = VecDeque::new();
+                visited[i - 1] = true;
+                queue.push_back(i);
+                while let Some(node) = queue.pop_front() {
+                    traversal.order.push(node);
+                    for j in self.neighbours(node) {
+                        if !visited[j - 1] {
+                            traversal.tree_edges.push((node, j));
+                            visited[j - 1] = true;
+                            queue.push_back(j);
                         }
                     }
                 }
             }
-            println!("{:?}", tree_edges)
         }
-        for node in traversal {
-            print!("{node} ");
-        }
-        print!("\n");
+        traversal
     }
 
-    pub fn bfs(&self, tree: bool){
-        let mut visited: Vec<usize> = Vec::new();
-        let mut traversal: Vec<usize> = Vec::new();
-        if tree == false{
-            for i in 1..=self.node_quantity {
-                if !visited.contains(&i) {
-                    let mut queue: VecDeque<usize> = VecDeque::new();
-                    visited.push(i);
-                    queue.push_back(i);
-                    while !queue.is_empty() {
-                        let node = queue.pop_front().unwrap();
-                        traversal.push(node);
-                        for j in &self.adj[node-1] {
-                            if !visited.contains(j) {
-                                visited.push(j.clone());
-                                queue.push_back(j.clone());
-                            }
+    //edges as given to `new`, an undirected edge is listed once
+    pub fn edges(&self) -> Vec<(usize, usize)> {
+        let mut edges: Vec<(usize, usize)> = Vec::new();
+        for u in 1..=self.node_quantity {
+            //an undirected self-loop is stored twice in its own list
+            let mut loop_seen = false;
+            for v in self.neighbours(u) {
+                match self.directionality {
+                    Directionality::Directed => edges.push((u, v)),
+                    Directionality::Undirected => {
+                        if u < v || (u == v && loop_seen) {
+                            edges.push((u, v));
                         }
-                    }
-                }
-            }
-        } else {
-            let mut tree_edges: Vec<(usize, usize)> = Vec::new();
-            for i in 1..=self.node_quantity {
-                if !visited.contains(&i) {
-                    let mut queue: VecDeque<usize> = VecDeque::new();
-                    visited.push(i);
-                    queue.push_back(i);
-                    while !queue.is_empty() {
-                        let node = queue.pop_front().unwrap();
-                        traversal.push(node);
-                        for j in &self.adj[node-1] {
-                            if !visited.contains(j) {
-                                tree_edges.push((node, j.clone()));
-                                visited.push(j.clone());
-                                queue.push_back(j.clone());
-                            }
+                        if u == v {
+                            loop_seen = !loop_seen;
                         }
                     }
                 }
             }
-            println!("{:?}", tree_edges)
         }
-        for node in traversal {
-            print!("{node} ");
-        }
-        print!("\n");
+        edges
     }
 
 
     //Kahn's Algorithm Topological Sort
     pub fn topological_sort(&self) -> Result<Vec<usize>, String> {
         let n = self.node_quantity;
-        let mut in_degrees: Vec<usize> = vec![0; n];
-        for from in &self.adj {
-            for to in from{
-                in_degrees[to - 1] += 1;
-            }
-        }
+        let mut in_degrees = self.in_degrees();
         let mut queue: VecDeque<usize> = VecDeque::new();
         for i in 0..n {
             if in_degrees[i] == 0 {
@@ -153,10 +150,10 @@ impl Graph {
         while !queue.is_empty() {
             let node = queue.pop_front().unwrap();
             order.push(node);
-            for to in &self.adj[node - 1] {
-                in_degrees[*to - 1] -= 1;
-                if in_degrees[*to - 1] == 0{
-                    queue.push_back(*to);
+            for to in self.neighbours(node) {
+                in_degrees[to - 1] -= 1;
+                if in_degrees[to - 1] == 0{
+                    queue.push_back(to);
                 }
             }
         }
@@ -167,25 +164,139 @@ impl Graph {
         }
     }
 
+    //Kahn's Algorithm with a min-heap instead of a FIFO queue,
+    //gives the lexicographically smallest order regardless of edge insertion order
+    pub fn topological_sort_lexicographic(&self) -> Result<Vec<usize>, String> {
+        let n = self.node_quantity;
+        let mut in_degrees = self.in_degrees();
+        let mut heap: BinaryHeap<Reverse<usize>> = BinaryHeap::new();
+        for (i, degree) in in_degrees.iter().enumerate() {
+            if *degree == 0 {
+                heap.push(Reverse(i + 1));
+            }
+        }
+        let mut order: Vec<usize> = Vec::with_capacity(n);
+        while let Some(Reverse(node)) = heap.pop() {
+            order.push(node);
+            for to in self.neighbours(node) {
+                in_degrees[to - 1] -= 1;
+                if in_degrees[to - 1] == 0 {
+                    heap.push(Reverse(to));
+                }
+            }
+        }
+        if order.len() != n {
+            Err("Not a DAG".to_string())
+        } else {
+            Ok(order)
+        }
+    }
+
+    //DFS Topological Sort (reversed post-order), back edge means a cycle
+    pub fn topological_sort_dfs(&self) -> Result<Vec<usize>, String> {
+        // 0 = unvisited, 1 = on the current path, 2 = finished
+        let mut state: Vec<u8> = vec![0; self.node_quantity];
+        let mut post_order: Vec<usize> = Vec::with_capacity(self.node_quantity);
+        for root in 1..=self.node_quantity {
+            if state[root - 1] != 0 {
+                continue;
+            }
+            //(node, index of the next neighbour to look at)
+            let mut stack: Vec<(usize, usize)> = vec![(root, 0)];
+            state[root - 1] = 1;
+            while let Some((node, next)) = stack.last_mut() {
+                if let Some(to) = self.adj.neighbours(*node).get(*next).map(|to| *to as usize) {
+                    *next += 1;
+                    match state[to - 1] {
+                        0 => {
+                            state[to - 1] = 1;
+                            stack.push((to, 0));
+                        },
+                        1 => return Err("Not a DAG".to_string()),
+                        _ => {}
+                    }
+                } else {
+                    state[*node - 1] = 2;
+                    post_order.push(*node);
+                    stack.pop();
+                }
+            }
+        }
+        post_order.reverse();
+        Ok(post_order)
+    }
+
+    //Iterator over every valid topological order (in lexicographic order), stops after `limit` orders
+    pub fn all_topological_sorts(&self, limit: usize) -> AllTopologicalSorts<'_> {
+        AllTopologicalSorts {
+            graph: self,
+            in_degrees: self.in_degrees(),
+            used: vec![false; self.node_quantity],
+            order: Vec::with_capacity(self.node_quantity),
+            cursor: 0,
+            emitted: 0,
+            limit,
+            done: false,
+        }
+    }
+
+    pub fn is_topological_order(&self, order: &[usize]) -> bool {
+        if order.len() != self.node_quantity {
+            return false;
+        }
+        let mut position: Vec<Option<usize>> = vec![None; self.node_quantity];
+        for (i, node) in order.iter().enumerate() {
+            if *node == 0 || *node > self.node_quantity || position[node - 1].is_some() {
+                return false;
+            }
+            position[node - 1] = Some(i);
+        }
+        for from in 1..=self.node_quantity {
+            for to in self.neighbours(from) {
+                if position[from - 1] >= position[to - 1] {
+                    return false;
+                }
+            }
+        }
+        true
+    }
+
+    fn in_degrees(&self) -> Vec<usize> {
+        let mut in_degrees: Vec<usize> = vec![0; self.node_quantity];
+        for to in &self.adj.targets {
+            in_degrees[*to as usize - 1] += 1;
+        }
+        in_degrees
+    }
+
     //Kosaraji's Algorithm
     pub fn find_sccs(&self) -> Vec<Vec<usize>> {
-        //transposes the graph
-        fn reverse_graph(graph: &Vec<Vec<usize>>) -> Vec<Vec<usize>> {
-            let mut rev_graph = vec![Vec::new(); graph.len()];
-            for (u, edges) in graph.iter().enumerate() {
-                for &v in edges {
-                    rev_graph[v - 1].push(u + 1);
+        //dfs from node, appends nodes once all their descendants are finished
+        fn finish_order(graph: &Csr, v: usize, visited: &mut [bool], order: &mut Vec<usize>) {
+            //(node, index of the next neighbour to look at)
+            let mut stack = vec![(v, 0)];
+            visited[v - 1] = true;
+            while let Some((u, next)) = stack.last_mut() {
+                if let Some(w) = graph.neighbours(*u).get(*next).map(|w| *w as usize) {
+                    *next += 1;
+                    if !visited[w - 1] {
+                        visited[w - 1] = true;
+                        stack.push((w, 0));
+                    }
+                } else {
+                    order.push(*u);
+                    stack.pop();
                 }
             }
-            rev_graph
         }
         //dfs form node
-        fn dfs(graph: &Vec<Vec<usize>>, v: usize, visited: &mut Vec<bool>, order: &mut Vec<usize>) {
+        fn dfs(graph: &Csr, v: usize, visited: &mut [bool], order: &mut Vec<usize>) {
             let mut stack = vec![v];
             while let Some(u) = stack.pop() {
                 if !visited[u - 1] {
                     visited[u - 1] = true;
-                    for &w in &graph[u - 1] {
+                    for &w in graph.neighbours(u) {
+                        let w = w as usize;
                         if !visited[w - 1] {
                             stack.push(w);
                         }
@@ -194,26 +305,26 @@ impl Graph {
                 }
             }
         }
-        let graph = self.adj.clone();
+        let graph = &self.adj;
         // Step 1: Compute the reverse graph.
-        let rev_graph = reverse_graph(&graph);
+        let rev_graph = graph.transpose();
     
         // Step 2: Perform DFS on the reverse graph to get the finishing times.
-        let mut visited = vec![false; graph.len()];
+        let mut visited = vec![false; self.node_quantity];
         let mut stack = Vec::new();
-        for i in 1..=graph.len() {
+        for i in 1..=self.node_quantity {
             if !visited[i - 1] {
-                dfs(&rev_graph, i, &mut visited, &mut stack);
+                finish_order(&rev_graph, i, &mut visited, &mut stack);
             }
         }
     
         // Step 3: Perform DFS on the original graph in the order of finishing times.
-        let mut visited = vec![false; graph.len()];
+        let mut visited = vec![false; self.node_quantity];
         let mut sccs = Vec::new();
         while let Some(v) = stack.pop() {
             if !visited[v - 1] {
                 let mut scc = Vec::new();
-                dfs(&graph, v, &mut visited, &mut scc);
+                dfs(graph, v, &mut visited, &mut scc);
                 sccs.push(scc);
             }
         }
@@ -223,6 +334,11 @@ impl Graph {
 
 
     pub fn is_bipartite(&self) -> bool{
+        self.bipartition().is_some()
+    }
+
+    //two color classes (red, blue), None if some edge joins two nodes of the same color
+    pub fn bipartition(&self) -> Option<(Vec<usize>, Vec<usize>)> {
         // -1 = uncolored, 0 = red, 1 = blue
         let mut color: Vec<i8> = vec![-1; self.node_quantity];
         //bfs queue (node, color)
@@ -232,27 +348,533 @@ impl Graph {
             if color[node - 1] == -1 {
                 queue.push_back((node, 0));
                 color[node - 1] = 0;
-                while !queue.is_empty() {
-                    //(node, color)
-                    let pair = queue.pop_front().unwrap();
-                    for connected_node in self.adj[pair.0 - 1].clone() {
+                while let Some((current, current_color)) = queue.pop_front() {
+                    for connected_node in self.neighbours(current) {
                         // two colors cannot be touching if it is a bigraph
-                        if color[connected_node - 1] == pair.1 {
-                            return false;
-                        }else if color[connected_node - 1] == -1 {
-                            if pair.1 == 0 {
-                                color[connected_node - 1] = 1;
-                            }else {
-                                color[connected_node - 1] = 0;
-                            }
+                        if color[connected_node - 1] == current_color {
+                            return None;
+                        } else if color[connected_node - 1] == -1 {
+                            color[connected_node - 1] = 1 - current_color;
                             queue.push_back((connected_node, color[connected_node - 1]))
                         }
                     }
-                    
                 }
             }
         }
-        return true;
+        let red = (1..=self.node_quantity).filter(|node| color[node - 1] == 0).collect();
+        let blue = (1..=self.node_quantity).filter(|node| color[node - 1] == 1).collect();
+        Some((red, blue))
+    }
+
+    //Cycles of a spanning forest: one cycle per non-tree edge, m - n + c cycles in total.
+    //Each cycle is returned as a closed sequence of nodes (the first node is not repeated)
+    pub fn fundamental_cycle_basis(&self) -> Result<Vec<Vec<usize>>, String> {
+        let (edges, incidence) = self.undirected_incidence()?;
+        let n = self.node_quantity;
+        let mut parent: Vec<Option<(usize, usize)>> = vec![None; n];
+        let mut depth: Vec<usize> = vec![0; n];
+        let mut visited = vec![false; n];
+        let mut tree_edge = vec![false; edges.len()];
+        for root in 0..n {
+            if visited[root] {
+                continue;
+            }
+            visited[root] = true;
+            let mut queue: VecDeque<usize> = VecDeque::from([root]);
+            while let Some(u) = queue.pop_front() {
+                for &(v, e) in &incidence[u] {
+                    if !visited[v] {
+                        visited[v] = true;
+                        parent[v] = Some((u, e));
+                        depth[v] = depth[u] + 1;
+                        tree_edge[e] = true;
+                        queue.push_back(v);
+                    }
+                }
+            }
+        }
+        let mut cycles: Vec<Vec<usize>> = Vec::new();
+        for (e, &(u, v)) in edges.iter().enumerate() {
+            if tree_edge[e] {
+                continue;
+            }
+            //climb from both ends to the lowest common ancestor
+            let (mut a, mut b) = (u, v);
+            let mut from_u = vec![a];
+            let mut from_v = vec![b];
+            while a != b {
+                if depth[a] >= depth[b] {
+                    a = parent[a].unwrap().0;
+                    from_u.push(a);
+                } else {
+                    b = parent[b].unwrap().0;
+                    from_v.push(b);
+                }
+            }
+            from_v.pop();
+            from_u.extend(from_v.into_iter().rev());
+            cycles.push(from_u.into_iter().map(|x| x + 1).collect());
+        }
+        Ok(cycles)
     }
 
-}
\ No newline at end of file
+    //Horton's algorithm: candidate cycles from every shortest path tree, taken shortest first
+    //while they stay linearly independent over GF(2). O(n * m^2) so only for small graphs
+    pub fn minimum_cycle_basis(&self) -> Result<Vec<Vec<usize>>, String> {
+        let (edges, incidence) = self.undirected_incidence()?;
+        let n = self.node_quantity;
+        let words = edges.len().div_ceil(64);
+        //(length, nodes, edge set)
+        let mut candidates: Vec<(usize, Vec<usize>, Vec<u64>)> = Vec::new();
+        for root in 0..n {
+            let mut parent: Vec<Option<(usize, usize)>> = vec![None; n];
+            let mut depth: Vec<Option<usize>> = vec![None; n];
+            //child of the root that the node hangs under
+            let mut branch: Vec<Option<usize>> = vec![None; n];
+            depth[root] = Some(0);
+            let mut queue: VecDeque<usize> = VecDeque::from([root]);
+            while let Some(u) = queue.pop_front() {
+                for &(v, e) in &incidence[u] {
+                    if depth[v].is_none() {
+                        depth[v] = Some(depth[u].unwrap() + 1);
+                        parent[v] = Some((u, e));
+                        branch[v] = if u == root { Some(v) } else { branch[u] };
+                        queue.push_back(v);
+                    }
+                }
+            }
+            for (e, &(u, v)) in edges.iter().enumerate() {
+                let (Some(du), Some(dv)) = (depth[u], depth[v]) else {
+                    continue;
+                };
+                let is_tree_edge = parent[v].is_some_and(|(_, pe)| pe == e) || parent[u].is_some_and(|(_, pe)| pe == e);
+                let disjoint = u == root || v == root || branch[u] != branch[v];
+                //self-loops are only taken from their own node
+                if is_tree_edge || !disjoint || (u == v && u != root) {
+                    continue;
+                }
+                let mut set = vec![0u64; words];
+                set[e / 64] ^= 1 << (e % 64);
+                let mut nodes: Vec<usize> = Vec::new();
+                let mut x = u;
+                while let Some((p, pe)) = parent[x] {
+                    nodes.push(x);
+                    set[pe / 64] ^= 1 << (pe % 64);
+                    x = p;
+                }
+                nodes.push(root);
+                nodes.reverse();
+                let mut tail: Vec<usize> = Vec::new();
+                let mut x = v;
+                while let Some((p, pe)) = parent[x] {
+                    tail.push(x);
+                    set[pe / 64] ^= 1 << (pe % 64);
+                    x = p;
+                }
+                if u != v {
+                    nodes.extend(tail);
+                }
+                candidates.push((du + dv + 1, nodes, set));
+            }
+        }
+        candidates.sort_by_key(|(length, _, _)| *length);
+
+        let dimension = self.fundamental_cycle_basis()?.len();
+        //gaussian elimination rows, kept with their lowest set bit as pivot
+        let mut rows: Vec<(usize, Vec<u64>)> = Vec::new();
+        let mut basis: Vec<Vec<usize>> = Vec::new();
+        for (_, nodes, mut set) in candidates {
+            if basis.len() == dimension {
+                break;
+            }
+            for (pivot, row) in &rows {
+                if set[pivot / 64] & (1 << (pivot % 64)) != 0 {
+                    for (a, b) in set.iter_mut().zip(row) {
+                        *a ^= b;
+                    }
+                }
+            }
+            if let Some(pivot) = lowest_bit(&set) {
+                rows.push((pivot, set));
+                basis.push(nodes.into_iter().map(|x| x + 1).collect());
+            }
+        }
+        Ok(basis)
+    }
+
+    //Length of the shortest cycle, BFS from every node. None if the graph is a forest
+    pub fn girth(&self) -> Result<Option<usize>, String> {
+        let (_, incidence) = self.undirected_incidence()?;
+        let n = self.node_quantity;
+        let mut girth: Option<usize> = None;
+        for root in 0..n {
+            let mut dist: Vec<Option<usize>> = vec![None; n];
+            let mut parent_edge: Vec<Option<usize>> = vec![None; n];
+            dist[root] = Some(0);
+            let mut queue: VecDeque<usize> = VecDeque::from([root]);
+            while let Some(u) = queue.pop_front() {
+                let du = dist[u].unwrap();
+                //nothing shorter can be found deeper in this tree
+                if girth.is_some_and(|g| 2 * du + 1 >= g) {
+                    break;
+                }
+                for &(v, e) in &incidence[u] {
+                    match dist[v] {
+                        None => {
+                            dist[v] = Some(du + 1);
+                            parent_edge[v] = Some(e);
+                            queue.push_back(v);
+                        },
+                        Some(dv) if parent_edge[u] != Some(e) => {
+                            let length = du + dv + 1;
+                            if girth.is_none_or(|g| length < g) {
+                                girth = Some(length);
+                            }
+                        },
+                        _ => {}
+                    }
+                }
+            }
+        }
+        Ok(girth)
+    }
+
+    //0-based edge list (each undirected edge once) and incidence lists of (neighbour, edge index)
+    fn undirected_incidence(&self) -> Result<(Vec<(usize, usize)>, Incidence), String> {
+        if self.directionality != Directionality::Undirected {
+            return Err("Not an undirected graph".to_string());
+        }
+        let edges: Vec<(usize, usize)> = self.edges().into_iter().map(|(u, v)| (u - 1, v - 1)).collect();
+        let mut incidence: Incidence = vec![Vec::new(); self.node_quantity];
+        for (e, &(u, v)) in edges.iter().enumerate() {
+            incidence[u].push((v, e));
+            if u != v {
+                incidence[v].push((u, e));
+            }
+        }
+        Ok((edges, incidence))
+    }
+
+}
+
+fn lowest_bit(set: &[u64]) -> Option<usize> {
+    set.iter()
+        .enumerate()
+        .find(|(_, word)| **word != 0)
+        .map(|(i, word)| i * 64 + word.trailing_zeros() as usize)
+}
+
+//Backtracking over the nodes with in-degree 0, state is kept between calls to `next`
+pub struct AllTopologicalSorts<'a> {
+    graph: &'a Graph,
+    in_degrees: Vec<usize>,
+    used: Vec<bool>,
+    order: Vec<usize>,
+    //0-based node to start looking from at the current depth
+    cursor: usize,
+    emitted: usize,
+    limit: usize,
+    done: bool,
+}
+
+impl<'a> AllTopologicalSorts<'a> {
+    fn choose(&mut self, node: usize) {
+        self.used[node] = true;
+        for to in self.graph.neighbours(node + 1) {
+            self.in_degrees[to - 1] -= 1;
+        }
+        self.order.push(node);
+    }
+
+    fn unchoose(&mut self) -> Option<usize> {
+        let node = self.order.pop()?;
+        self.used[node] = false;
+        for to in self.graph.neighbours(node + 1) {
+            self.in_degrees[to - 1] += 1;
+        }
+        Some(node)
+    }
+}
+
+impl<'a> Iterator for AllTopologicalSorts<'a> {
+    type Item = Vec<usize>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done || self.emitted >= self.limit {
+            return None;
+        }
+        let n = self.graph.node_quantity;
+        if n == 0 {
+            self.done = true;
+            self.emitted += 1;
+            return Some(Vec::new());
+        }
+        loop {
+            let candidate = (self.cursor..n).find(|&v| !self.used[v] && self.in_degrees[v] == 0);
+            match candidate {
+                Some(node) => {
+                    self.choose(node);
+                    self.cursor = 0;
+                    if self.order.len() == n {
+                        let result = self.order.iter().map(|v| v + 1).collect();
+                        self.emitted += 1;
+                        let last = self.unchoose().unwrap();
+                        self.cursor = last + 1;
+                        return Some(result);
+                    }
+                },
+                None => match self.unchoose() {
+                    Some(last) => self.cursor = last + 1,
+                    None => {
+                        self.done = true;
+                        return None;
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::graph::*;
+
+    #[test]
+    fn topological_sort_lexicographic_test() {
+        let graph = Graph::new(Directionality::Directed, 4, vec![(3, 1), (2, 4)]);
+        assert_eq!(graph.topological_sort(), Ok(vec![2, 3, 4, 1]));
+        assert_eq!(graph.topological_sort_lexicographic(), Ok(vec![2, 3, 1, 4]));
+    }
+
+    #[test]
+    fn topological_sort_dfs_test() {
+        let graph = Graph::new(Directionality::Directed, 6, vec![(1, 3), (1, 2), (3, 5), (3, 6), (2, 3), (2, 4), (2, 5), (4, 5), (5, 6)]);
+        assert!(graph.is_topological_order(&graph.topological_sort_dfs().unwrap()));
+        let cyclic = Graph::new(Directionality::Directed, 3, vec![(1, 2), (2, 3), (3, 1)]);
+        assert!(cyclic.topological_sort_dfs().is_err());
+        assert!(cyclic.topological_sort_lexicographic().is_err());
+    }
+
+    #[test]
+    fn all_topological_sorts_test() {
+        let graph = Graph::new(Directionality::Directed, 4, vec![(3, 1), (2, 4)]);
+        let orders: Vec<Vec<usize>> = graph.all_topological_sorts(usize::MAX).collect();
+        assert_eq!(orders.len(), 6);
+        assert_eq!(orders[0], graph.topological_sort_lexicographic().unwrap());
+        assert!(orders.iter().all(|order| graph.is_topological_order(order)));
+        assert_eq!(graph.all_topological_sorts(4).count(), 4);
+        let cyclic = Graph::new(Directionality::Directed, 3, vec![(1, 2), (2, 3), (3, 1)]);
+        assert_eq!(cyclic.all_topological_sorts(10).count(), 0);
+    }
+
+    #[test]
+    fn is_topological_order_test() {
+        let graph = Graph::new(Directionality::Directed, 3, vec![(1, 2), (2, 3)]);
+        assert!(graph.is_topological_order(&[1, 2, 3]));
+        assert!(!graph.is_topological_order(&[2, 1, 3]));
+        assert!(!graph.is_topological_order(&[1, 2]));
+        assert!(!graph.is_topological_order(&[1, 1, 3]));
+        assert!(!graph.is_topological_order(&[1, 2, 4]));
+    }
+
+    fn is_cycle(graph: &Graph, cycle: &[usize]) -> bool {
+        let closing = [cycle[cycle.len() - 1], cycle[0]];
+        cycle.windows(2).chain(std::iter::once(&closing[..])).all(|pair| graph.neighbours(pair[0]).any(|to| to == pair[1]))
+    }
+
+    #[test]
+    fn fundamental_cycle_basis_test() {
+        let k4 = Graph::new(Directionality::Undirected, 4, vec![(1, 2), (1, 3), (1, 4), (2, 3), (2, 4), (3, 4)]);
+        let basis = k4.fundamental_cycle_basis().unwrap();
+        assert_eq!(basis.len(), 3);
+        assert!(basis.iter().all(|cycle| is_cycle(&k4, cycle)));
+        let forest = Graph::new(Directionality::Undirected, 5, vec![(1, 2), (2, 3), (4, 5)]);
+        assert!(forest.fundamental_cycle_basis().unwrap().is_empty());
+        let multi = Graph::new(Directionality::Undirected, 2, vec![(1, 2), (1, 2), (2, 2)]);
+        assert_eq!(multi.fundamental_cycle_basis().unwrap(), vec![vec![1, 2], vec![2]]);
+        let directed = Graph::new(Directionality::Directed, 2, vec![(1, 2)]);
+        assert!(directed.fundamental_cycle_basis().is_err());
+    }
+
+    #[test]
+    fn minimum_cycle_basis_test() {
+        let diamond = Graph::new(Directionality::Undirected, 4, vec![(1, 2), (2, 3), (3, 4), (4, 1), (1, 3)]);
+        let basis = diamond.minimum_cycle_basis().unwrap();
+        assert_eq!(basis.len(), 2);
+        assert!(basis.iter().all(|cycle| cycle.len() == 3 && is_cycle(&diamond, cycle)));
+        let k4 = Graph::new(Directionality::Undirected, 4, vec![(1, 2), (1, 3), (1, 4), (2, 3), (2, 4), (3, 4)]);
+        let basis = k4.minimum_cycle_basis().unwrap();
+        assert_eq!(basis.iter().map(|cycle| cycle.len()).sum::<usize>(), 9);
+    }
+
+    #[test]
+    fn girth_test() {
+        let petersen = Graph::new(Directionality::Undirected, 10, vec![(1, 2), (2, 3), (3, 4), (4, 5), (5, 1), (1, 6), (2, 7), (3, 8), (4, 9), (5, 10), (6, 8), (8, 10), (10, 7), (7, 9), (9, 6)]);
+        assert_eq!(petersen.girth(), Ok(Some(5)));
+        let forest = Graph::new(Directionality::Undirected, 4, vec![(1, 2), (2, 3)]);
+        assert_eq!(forest.girth(), Ok(None));
+        let multi = Graph::new(Directionality::Undirected, 3, vec![(1, 2), (2, 3), (3, 1), (2, 3)]);
+        assert_eq!(multi.girth(), Ok(Some(2)));
+        let self_loop = Graph::new(Directionality::Undirected, 2, vec![(1, 2), (2, 2)]);
+        assert_eq!(self_loop.girth(), Ok(Some(1)));
+    }
+
+    //Randomised cross-validation against brute force on small graphs, proptest shrinks failures
+    //to the smallest node count and edge list that still break the property
+    use proptest::prelude::*;
+
+    fn arbitrary_graph(directionality: Directionality) -> impl Strategy<Value = Graph> {
+        (1..=8usize).prop_flat_map(move |n| {
+            let directionality = directionality.clone();
+            prop::collection::vec((1..=n, 1..=n), 0..=16)
+                .prop_map(move |edges| Graph::new(directionality.clone(), n, edges))
+        })
+    }
+
+    fn any_graph() -> impl Strategy<Value = Graph> {
+        prop_oneof![arbitrary_graph(Directionality::Directed), arbitrary_graph(Directionality::Undirected)]
+    }
+
+    //reachable[u][v] for paths of length >= 1 (Floyd-Warshall closure)
+    fn reachability(graph: &Graph) -> Vec<Vec<bool>> {
+        let n = graph.node_quantity;
+        let mut reachable = vec![vec![false; n]; n];
+        for u in 1..=n {
+            for v in graph.neighbours(u) {
+                reachable[u - 1][v - 1] = true;
+            }
+        }
+        for k in 0..n {
+            for i in 0..n {
+                for j in 0..n {
+                    reachable[i][j] |= reachable[i][k] && reachable[k][j];
+                }
+            }
+        }
+        reachable
+    }
+
+    fn is_permutation(order: &[usize], n: usize) -> bool {
+        let mut sorted = order.to_vec();
+        sorted.sort();
+        sorted == (1..=n).collect::<Vec<usize>>()
+    }
+
+    fn check_spanning_forest(graph: &Graph, traversal: &Traversal) -> Result<(), TestCaseError> {
+        let n = graph.node_quantity;
+        prop_assert!(is_permutation(&traversal.order, n));
+        let mut position = vec![0; n];
+        for (i, node) in traversal.order.iter().enumerate() {
+            position[node - 1] = i;
+        }
+        let mut has_parent = vec![false; n];
+        for &(parent, child) in &traversal.tree_edges {
+            prop_assert!(graph.neighbours(parent).any(|to| to == child));
+            prop_assert!(!has_parent[child - 1]);
+            prop_assert!(position[parent - 1] < position[child - 1]);
+            has_parent[child - 1] = true;
+        }
+        //every root starts a new tree, so no node before it can reach it
+        let reachable = reachability(graph);
+        for root in (1..=n).filter(|v| !has_parent[v - 1]) {
+            for earlier in &traversal.order[..position[root - 1]] {
+                prop_assert!(!reachable[earlier - 1][root - 1]);
+            }
+        }
+        Ok(())
+    }
+
+    proptest! {
+        #[test]
+        fn sccs_are_mutual_reachability_classes(graph in any_graph()) {
+            let n = graph.node_quantity;
+            let sccs = graph.find_sccs();
+            prop_assert!(is_permutation(&sccs.concat(), n));
+            let mut component = vec![0; n];
+            for (i, scc) in sccs.iter().enumerate() {
+                for v in scc {
+                    component[v - 1] = i;
+                }
+            }
+            let reachable = reachability(&graph);
+            for u in 0..n {
+                for v in 0..n {
+                    let mutual = u == v || (reachable[u][v] && reachable[v][u]);
+                    prop_assert_eq!(component[u] == component[v], mutual, "nodes {} and {}", u + 1, v + 1);
+                }
+            }
+        }
+
+        #[test]
+        fn topological_sorts_respect_every_edge(graph in any_graph()) {
+            let reachable = reachability(&graph);
+            let acyclic = (0..graph.node_quantity).all(|v| !reachable[v][v]);
+            for result in [graph.topological_sort(), graph.topological_sort_lexicographic(), graph.topological_sort_dfs()] {
+                match result {
+                    Ok(order) => prop_assert!(acyclic && graph.is_topological_order(&order)),
+                    Err(_) => prop_assert!(!acyclic),
+                }
+            }
+            if acyclic {
+                let mut orders = graph.all_topological_sorts(50);
+                prop_assert_eq!(orders.next(), graph.topological_sort_lexicographic().ok());
+                prop_assert!(orders.all(|order| graph.is_topological_order(&order)));
+            }
+        }
+
+        #[test]
+        fn bipartite_matches_brute_force_coloring(graph in arbitrary_graph(Directionality::Undirected)) {
+            let n = graph.node_quantity;
+            let edges = graph.edges();
+            let colorable = (0..1u32 << n).any(|mask| {
+                edges.iter().all(|(u, v)| (mask >> (u - 1)) & 1 != (mask >> (v - 1)) & 1)
+            });
+            prop_assert_eq!(graph.is_bipartite(), colorable);
+            if let Some((red, blue)) = graph.bipartition() {
+                prop_assert!(is_permutation(&[red.clone(), blue.clone()].concat(), n));
+                prop_assert!(edges.iter().all(|(u, v)| red.contains(u) != red.contains(v)));
+            }
+        }
+
+        #[test]
+        fn traversals_are_spanning_forests(graph in any_graph()) {
+            check_spanning_forest(&graph, &graph.dfs_traversal())?;
+            let bfs = graph.bfs_traversal();
+            check_spanning_forest(&graph, &bfs)?;
+            //the first BFS tree holds shortest distances from node 1
+            let mut depth: Vec<Option<usize>> = vec![None; graph.node_quantity];
+            depth[0] = Some(0);
+            for &(parent, child) in &bfs.tree_edges {
+                if let Some(d) = depth[parent - 1] {
+                    depth[child - 1] = Some(d + 1);
+                }
+            }
+            for v in graph.neighbours(1) {
+                prop_assert!(depth[v - 1].is_some_and(|d| d <= 1));
+            }
+            for u in 1..=graph.node_quantity {
+                if let Some(du) = depth[u - 1] {
+                    for v in graph.neighbours(u) {
+                        prop_assert!(depth[v - 1].is_some_and(|dv| dv <= du + 1));
+                    }
+                }
+            }
+        }
+
+        #[test]
+        fn cycle_bases_have_the_cyclomatic_dimension(graph in arbitrary_graph(Directionality::Undirected)) {
+            let n = graph.node_quantity;
+            let reachable = reachability(&graph);
+            let components = (0..n).filter(|&v| (0..v).all(|u| !reachable[u][v])).count();
+            let dimension = graph.edges().len() + components - n;
+            let fundamental = graph.fundamental_cycle_basis().unwrap();
+            let minimum = graph.minimum_cycle_basis().unwrap();
+            prop_assert_eq!(fundamental.len(), dimension);
+            prop_assert_eq!(minimum.len(), dimension);
+            prop_assert!(fundamental.iter().chain(&minimum).all(|cycle| is_cycle(&graph, cycle)));
+            let total = |basis: &Vec<Vec<usize>>| basis.iter().map(|cycle| cycle.len()).sum::<usize>();
+            prop_assert!(total(&minimum) <= total(&fundamental));
+            prop_assert_eq!(graph.girth().unwrap(), minimum.iter().map(|cycle| cycle.len()).min());
+        }
+    }
+}
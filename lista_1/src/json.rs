@@ -0,0 +1,148 @@
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::graph::{Directionality, Graph, Traversal};
+
+//On-disk form of a graph, nodes are numbered from 1 like in `Graph::new`
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct GraphJson {
+    pub directionality: Directionality,
+    pub node_quantity: usize,
+    pub edges: Vec<(usize, usize)>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub labels: Option<Vec<String>>,
+}
+
+impl GraphJson {
+    pub fn from_graph(graph: &Graph, labels: Option<Vec<String>>) -> Self {
+        GraphJson {
+            directionality: graph.directionality.clone(),
+            node_quantity: graph.node_quantity,
+            edges: graph.edges(),
+            labels,
+        }
+    }
+
+    pub fn to_graph(&self) -> Result<Graph, String> {
+        check_labels(self.node_quantity, &self.labels)?;
+        for &(from, to) in &self.edges {
+            if from == 0 || to == 0 || from > self.node_quantity || to > self.node_quantity {
+                return Err(format!("Edge ({from}, {to}) out of range 1..={}", self.node_quantity));
+            }
+        }
+        Ok(Graph::new(self.directionality.clone(), self.node_quantity, self.edges.clone()))
+    }
+}
+
+//Results of the lista_1 algorithms, tagged with the algorithm name
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+#[serde(tag = "algorithm", rename_all = "snake_case")]
+pub enum AlgorithmResult {
+    Dfs(Traversal),
+    Bfs(Traversal),
+    Sccs { components: Vec<Vec<usize>> },
+    //order is null when the graph is not a DAG
+    TopologicalSort { order: Option<Vec<usize>> },
+    //both sides are empty when the graph is not bipartite
+    Bipartition { bipartite: bool, red: Vec<usize>, blue: Vec<usize> },
+}
+
+impl AlgorithmResult {
+    pub fn dfs(graph: &Graph) -> Self {
+        AlgorithmResult::Dfs(graph.dfs_traversal())
+    }
+
+    pub fn bfs(graph: &Graph) -> Self {
+        AlgorithmResult::Bfs(graph.bfs_traversal())
+    }
+
+    pub fn sccs(graph: &Graph) -> Self {
+        AlgorithmResult::Sccs { components: graph.find_sccs() }
+    }
+
+    pub fn topological_sort(graph: &Graph) -> Self {
+        AlgorithmResult::TopologicalSort { order: graph.topological_sort().ok() }
+    }
+
+    pub fn bipartition(graph: &Graph) -> Self {
+        match graph.bipartition() {
+            Some((red, blue)) => AlgorithmResult::Bipartition { bipartite: true, red, blue },
+            None => AlgorithmResult::Bipartition { bipartite: false, red: Vec::new(), blue: Vec::new() },
+        }
+    }
+
+    //labels, if any, have to name every node of the graph the result was computed on
+    pub fn with_labels(self, node_quantity: usize, labels: Option<Vec<String>>) -> Result<LabelledResult, String> {
+        check_labels(node_quantity, &labels)?;
+        Ok(LabelledResult { result: self, labels })
+    }
+}
+
+//A result next to the labels of its graph, node i of the result is labels[i - 1]
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct LabelledResult {
+    #[serde(flatten)]
+    pub result: AlgorithmResult,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub labels: Option<Vec<String>>,
+}
+
+fn check_labels(node_quantity: usize, labels: &Option<Vec<String>>) -> Result<(), String> {
+    match labels {
+        Some(labels) if labels.len() != node_quantity => Err(format!("Expected {} labels, got {}", node_quantity, labels.len())),
+        _ => Ok(()),
+    }
+}
+
+pub fn to_json<T: Serialize>(value: &T) -> String {
+    serde_json::to_string(value).expect("lista_1 types always serialize")
+}
+
+pub fn from_json<T: DeserializeOwned>(json: &str) -> Result<T, String> {
+    serde_json::from_str(json).map_err(|e| e.to_string())
+}
+
+//the graph with the labels of its nodes, if the JSON has any
+pub fn graph_from_json(json: &str) -> Result<(Graph, Option<Vec<String>>), String> {
+    let graph_json = from_json::<GraphJson>(json)?;
+    let graph = graph_json.to_graph()?;
+    Ok((graph, graph_json.labels))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::json::*;
+
+    #[test]
+    fn graph_round_trip_test() {
+        let graph = Graph::new(Directionality::Undirected, 3, vec![(1, 2), (2, 3), (3, 3)]);
+        let json = to_json(&GraphJson::from_graph(&graph, Some(vec!["a".into(), "b".into(), "c".into()])));
+        assert_eq!(json, r#"{"directionality":"undirected","node_quantity":3,"edges":[[1,2],[2,3],[3,3]],"labels":["a","b","c"]}"#);
+        let (parsed, labels) = graph_from_json(&json).unwrap();
+        assert_eq!(parsed.edges(), graph.edges());
+        assert_eq!(parsed.directionality, Directionality::Undirected);
+        assert_eq!(to_json(&GraphJson::from_graph(&parsed, labels)), json);
+        assert_eq!(graph_from_json(r#"{"directionality":"directed","node_quantity":1,"edges":[]}"#).unwrap().1, None);
+    }
+
+    #[test]
+    fn graph_from_json_errors_test() {
+        assert!(graph_from_json(r#"{"directionality":"directed","node_quantity":2,"edges":[[1,3]]}"#).is_err());
+        assert!(graph_from_json(r#"{"directionality":"directed","node_quantity":2,"edges":[],"labels":["a"]}"#).is_err());
+        assert!(graph_from_json(r#"{"directionality":"sideways","node_quantity":2,"edges":[]}"#).is_err());
+    }
+
+    #[test]
+    fn algorithm_result_schema_test() {
+        let graph = Graph::new(Directionality::Directed, 3, vec![(1, 2), (2, 3)]);
+        assert_eq!(to_json(&AlgorithmResult::topological_sort(&graph)), r#"{"algorithm":"topological_sort","order":[1,2,3]}"#);
+        assert_eq!(to_json(&AlgorithmResult::bfs(&graph)), r#"{"algorithm":"bfs","order":[1,2,3],"tree_edges":[[1,2],[2,3]]}"#);
+        assert_eq!(to_json(&AlgorithmResult::bipartition(&graph)), r#"{"algorithm":"bipartition","bipartite":true,"red":[1,3],"blue":[2]}"#);
+        let sccs = AlgorithmResult::sccs(&graph);
+        assert_eq!(from_json::<AlgorithmResult>(&to_json(&sccs)), Ok(sccs.clone()));
+        let labelled = sccs.clone().with_labels(3, Some(vec!["a".into(), "b".into(), "c".into()])).unwrap();
+        assert_eq!(to_json(&labelled), r#"{"algorithm":"sccs","components":[[3],[2],[1]],"labels":["a","b","c"]}"#);
+        assert_eq!(from_json::<LabelledResult>(&to_json(&labelled)), Ok(labelled));
+        assert_eq!(to_json(&AlgorithmResult::dfs(&graph).with_labels(3, None).unwrap()), to_json(&AlgorithmResult::dfs(&graph)));
+        assert_eq!(sccs.with_labels(3, Some(vec!["a".into()])), Err("Expected 3 labels, got 1".to_string()));
+    }
+}
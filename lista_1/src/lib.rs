@@ -0,0 +1,3 @@
+pub mod graph;
+pub mod json;
+pub mod stream;
@@ -1,24 +1,19 @@
 use std::{io, fs};
-use std::fs::File;
-use std::io::BufRead;
-use std::path::Path;
-use dialoguer::{console::Term, theme::ColorfulTheme, Select};
+use dialoguer::{console::Term, theme::ColorfulTheme, Input, Select};
+use serde::Serialize;
 
-mod graph;
-use graph::*;
+use lib::graph::*;
+use lib::json::*;
+use lib::stream::*;
 
-fn str_to_edge(string: &String) -> (usize, usize){
-    let mut nodes: Vec<usize> = Vec::new();
-    for node in string.split_whitespace(){
-        nodes.push(node.trim().parse().expect("Should be a number!"));
+fn report_progress(progress: Progress) {
+    //only worth showing for the big test_data sets
+    if progress.edge_quantity >= 1 << 24 {
+        eprint!("\rpass {}: {}/{} edges", progress.pass, progress.edges_read, progress.edge_quantity);
+        if progress.edges_read == progress.edge_quantity {
+            eprintln!();
+        }
     }
-    (nodes.get(0).unwrap().to_owned(), nodes.get(1).unwrap().to_owned())
-}
-
-fn read_lines<P>(filename: P) -> io::Result<io::Lines<io::BufReader<File>>>
-where P: AsRef<Path>, {
-    let file = File::open(filename)?;
-    Ok(io::BufReader::new(file).lines())
 }
 
 fn _gen_graph_from_console() ->  Graph{
@@ -46,7 +41,8 @@ fn _gen_graph_from_console() ->  Graph{
         io::stdin()
             .read_line(&mut edge)
             .expect("Failed to read line");
-        edges.push(str_to_edge(&edge));
+        let nodes: Vec<usize> = edge.split_whitespace().map(|node| node.parse().expect("Should be a number!")).collect();
+        edges.push((nodes[0], nodes[1]));
         edge = String::from("");
     } 
 
@@ -57,7 +53,11 @@ fn _gen_graph_from_console() ->  Graph{
     }
 }
 
-fn test1(){
+fn print_json<T: Serialize>(source: &str, result: T) {
+    println!("{}", to_json(&serde_json::json!({"source": source, "result": result})));
+}
+
+fn test1(json: bool){
     let mut graphs: Vec<Graph> = Vec::new();
     graphs.push(Graph::new(Directionality::Directed, 6, vec![(1, 3), (1, 2), (3, 5), (3, 6), (2, 3), (2, 4), (2, 5), (4, 5), (5, 6)]));
     graphs.push(Graph::new(Directionality::Undirected, 6, vec![(1, 3), (1, 2), (3, 5), (3, 6), (2, 3), (2, 4), (2, 5), (4, 5), (5, 6)]));
@@ -65,116 +65,142 @@ fn test1(){
     graphs.push(Graph::new(Directionality::Undirected, 8, vec![(1, 2), (1, 4), (2, 3), (2, 6), (3, 4), (4, 8), (5, 1), (5, 8), (6, 5), (6, 7), (7, 3), (8, 7)]));
     graphs.push(Graph::new(Directionality::Directed, 9, vec![(1, 2), (1, 3), (1, 5), (2, 4), (2, 5), (3, 5), (3, 6), (4, 5), (4, 8), (5, 6), (6, 7), (6, 9), (7, 4), (7, 5), (7, 8), (9, 7), (9, 8)]));
     graphs.push(Graph::new(Directionality::Undirected, 9, vec![(1, 2), (1, 3), (1, 5), (2, 4), (2, 5), (3, 5), (3, 6), (4, 5), (4, 8), (5, 6), (6, 7), (6, 9), (7, 4), (7, 5), (7, 8), (9, 7), (9, 8)]));
-    for graph in graphs{
+    for (i, graph) in graphs.iter().enumerate(){
+        if json {
+            let source = format!("test1/{}", i + 1);
+            print_json(&source, AlgorithmResult::dfs(graph));
+            print_json(&source, AlgorithmResult::bfs(graph));
+            continue;
+        }
         println!("dfs:");
         graph.dfs(true);
         println!("bfs:");
         graph.bfs(true);
     }
-    println!("\n");
+    if !json {
+        println!("\n");
+    }
 }
 
-fn test2(){
+fn test2(json: bool){
     let paths = fs::read_dir("./test_data/2").unwrap();
     for path in paths {
-        let graph: Graph;
-        if let Ok(mut lines) = read_lines(path.unwrap().path().display().to_string()) {
-            let directionality = lines.next().unwrap().unwrap();
-            let node_quantity: usize = lines.next().unwrap().unwrap().parse().unwrap();
-            lines.next();
-            let mut edges:Vec<(usize, usize)> = Vec::new();
-            for line in lines {
-                if let Ok(ip) = line {
-                    edges.push(str_to_edge(&ip))
-                }
+        let source = path.unwrap().path().display().to_string();
+        let graph = match load_graph(&source, report_progress) {
+            Ok(graph) => graph,
+            Err(e) => {
+                eprintln!("{source}: {e}");
+                continue;
             }
-            if directionality == "D"{
-                graph = Graph::new(Directionality::Directed, node_quantity, edges);
-            }else {
-                graph = Graph::new(Directionality::Directed, node_quantity, edges);
+        };
+        if json {
+            print_json(&source, AlgorithmResult::topological_sort(&graph));
+            continue;
+        }
+        let result = graph.topological_sort();
+        if graph.node_quantity <= 200{
+            match result {
+                Ok(sorted) => println!("{:?}", sorted),
+                Err(e) => println!("{e}")
             }
-            let result = graph.topological_sort();
-            if graph.node_quantity <= 200{
-                match result {
-                    Ok(sorted) => println!("{:?}", sorted),
-                    Err(e) => println!("{e}")
-                }
-            } else {
-                match result {
-                    Ok(_) => println!("DAG"),
-                    Err(_) => println!("Not a DAG")
-                }
+        } else {
+            match result {
+                Ok(_) => println!("DAG"),
+                Err(_) => println!("Not a DAG")
             }
         }
     }
-    println!("\n");
+    if !json {
+        println!("\n");
+    }
 }
 
-fn test3(){
+fn test3(json: bool){
     let paths = fs::read_dir("./test_data/3").unwrap();
     for path in paths {
-        let graph: Graph;
-        if let Ok(mut lines) = read_lines(path.unwrap().path().display().to_string()) {
-            let directionality = lines.next().unwrap().unwrap();
-            let node_quantity: usize = lines.next().unwrap().unwrap().parse().unwrap();
-            lines.next();
-            let mut edges:Vec<(usize, usize)> = Vec::new();
-            for line in lines {
-                if let Ok(ip) = line {
-                    edges.push(str_to_edge(&ip))
-                }
-            }
-            if directionality == "D"{
-                graph = Graph::new(Directionality::Directed, node_quantity, edges);
-            }else {
-                graph = Graph::new(Directionality::Directed, node_quantity, edges);
-            }
-            let sccs = graph.find_sccs();
-            println!("num of SCCs = {:?}", sccs.len());
-            let mut scc_lengths: Vec<usize> = Vec::new();
-            for i in sccs.clone(){
-                scc_lengths.push(i.len());
-            }
-            println!("num of element in each SCC = {:?}", scc_lengths);
-            if node_quantity <= 200 {
-                println!("SCC = {:?}", sccs);
+        let source = path.unwrap().path().display().to_string();
+        let graph = match load_graph(&source, report_progress) {
+            Ok(graph) => graph,
+            Err(e) => {
+                eprintln!("{source}: {e}");
+                continue;
             }
+        };
+        if json {
+            print_json(&source, AlgorithmResult::sccs(&graph));
+            continue;
         }
+        let sccs = graph.find_sccs();
+        println!("num of SCCs = {:?}", sccs.len());
+        let mut scc_lengths: Vec<usize> = Vec::new();
+        for i in sccs.clone(){
+            scc_lengths.push(i.len());
+        }
+        println!("num of element in each SCC = {:?}", scc_lengths);
+        if graph.node_quantity <= 200 {
+            println!("SCC = {:?}", sccs);
+        }
+    }
+    if !json {
+        println!("\n");
     }
-    println!("\n");
 }
 
-fn test4(){
+fn test4(json: bool){
     let paths = fs::read_dir("./test_data/4").unwrap();
     for path in paths {
-        let graph: Graph;
-        if let Ok(mut lines) = read_lines(path.unwrap().path().display().to_string()) {
-            let directionality = lines.next().unwrap().unwrap();
-            let node_quantity: usize = lines.next().unwrap().unwrap().parse().unwrap();
-            lines.next();
-            let mut edges:Vec<(usize, usize)> = Vec::new();
-            for line in lines {
-                if let Ok(ip) = line {
-                    edges.push(str_to_edge(&ip))
-                }
-            }
-            if directionality == "D"{
-                graph = Graph::new(Directionality::Directed, node_quantity, edges);
-            }else {
-                graph = Graph::new(Directionality::Directed, node_quantity, edges);
-            }
-            if graph.is_bipartite() {
-                println!("Graph is bipartite");
-            } else {
-                println!("Graph NOT bipartite");
+        let source = path.unwrap().path().display().to_string();
+        let graph = match load_graph(&source, report_progress) {
+            Ok(graph) => graph,
+            Err(e) => {
+                eprintln!("{source}: {e}");
+                continue;
             }
+        };
+        if json {
+            print_json(&source, AlgorithmResult::bipartition(&graph));
+            continue;
+        }
+        if graph.is_bipartite() {
+            println!("Graph is bipartite");
+        } else {
+            println!("Graph NOT bipartite");
+        }
+    }
+    if !json {
+        println!("\n");
+    }
+}
+
+//every algorithm on a graph from a JSON file, always printed as JSON with the labels of the file
+fn json_graph() {
+    let source : String = Input::with_theme(&ColorfulTheme::default())
+        .with_prompt("Provide JSON graph file")
+        .interact_text()
+        .unwrap();
+    let loaded = fs::read_to_string(&source).map_err(|e| e.to_string()).and_then(|json| graph_from_json(&json));
+    let (graph, labels) = match loaded {
+        Ok(loaded) => loaded,
+        Err(e) => {
+            eprintln!("{source}: {e}");
+            return;
         }
+    };
+    let results = [
+        AlgorithmResult::dfs(&graph),
+        AlgorithmResult::bfs(&graph),
+        AlgorithmResult::sccs(&graph),
+        AlgorithmResult::topological_sort(&graph),
+        AlgorithmResult::bipartition(&graph),
+    ];
+    for result in results {
+        print_json(&source, result.with_labels(graph.node_quantity, labels.clone()).expect("labels checked on import"));
     }
-    println!("\n");
 }
 
 fn main() {
-    let items = vec!["Test 1", "Test 2", "Test 3", "Test 4", "Exit"];
+    let items = vec!["Test 1", "Test 2", "Test 3", "Test 4", "JSON graph file", "Toggle JSON output", "Exit"];
+    let mut json = false;
     loop {
         let selection = Select::with_theme(&ColorfulTheme::default())
         .items(&items)
@@ -183,10 +209,15 @@ fn main() {
         .expect("failed");
 
         match selection.unwrap() + 1 {
-            1 => test1(),
-            2 => test2(),
-            3 => test3(),
-            4 => test4(),
+            1 => test1(json),
+            2 => test2(json),
+            3 => test3(json),
+            4 => test4(json),
+            5 => json_graph(),
+            6 => {
+                json = !json;
+                eprintln!("JSON output {}", if json { "on" } else { "off" });
+            },
             _ => break
         }
     }
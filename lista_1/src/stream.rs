@@ -0,0 +1,355 @@
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+
+use crate::graph::{Directionality, Graph};
+
+//how often (in edges) the progress callback is called
+const PROGRESS_STEP: usize = 1 << 20;
+const BUFFER_SIZE: usize = 1 << 20;
+const CHANGED: &str = "file changed between the two passes";
+
+//First three lines of a test_data file: D/U, number of nodes, number of edges
+#[derive(Debug, PartialEq, Clone)]
+pub struct EdgeListHeader {
+    pub directionality: Directionality,
+    pub node_quantity: usize,
+    pub edge_quantity: usize,
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Progress {
+    //1 while counting degrees, 2 while filling the adjacency
+    pub pass: u8,
+    pub edges_read: usize,
+    pub edge_quantity: usize,
+}
+
+//Parses the edge list straight from the read buffer, no String is allocated per line
+pub struct EdgeListReader<R: BufRead> {
+    reader: R,
+    header: EdgeListHeader,
+    line: usize,
+}
+
+impl EdgeListReader<BufReader<File>> {
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, String> {
+        let file = File::open(&path).map_err(|e| format!("{}: {e}", path.as_ref().display()))?;
+        EdgeListReader::new(BufReader::with_capacity(BUFFER_SIZE, file))
+    }
+}
+
+impl<R: BufRead> EdgeListReader<R> {
+    pub fn new(reader: R) -> Result<Self, String> {
+        let mut reader = EdgeListReader {
+            reader,
+            header: EdgeListHeader { directionality: Directionality::Directed, node_quantity: 0, edge_quantity: 0 },
+            line: 1,
+        };
+        reader.header.directionality = match reader.peek_non_whitespace()? {
+            Some(b'D') => Directionality::Directed,
+            Some(b'U') => Directionality::Undirected,
+            _ => return Err(format!("line {}: expected D or U", reader.line)),
+        };
+        reader.reader.consume(1);
+        reader.header.node_quantity = reader.read_number()?.ok_or("missing number of nodes")?;
+        reader.header.edge_quantity = reader.read_number()?.ok_or("missing number of edges")?;
+        Ok(reader)
+    }
+
+    pub fn header(&self) -> &EdgeListHeader {
+        &self.header
+    }
+
+    //next edge, checked against the number of nodes from the header
+    pub fn next_edge(&mut self) -> Result<Option<(usize, usize)>, String> {
+        let Some(from) = self.read_number()? else {
+            return Ok(None);
+        };
+        let line = self.line;
+        let to = self.read_number()?.ok_or(format!("line {line}: edge without an end"))?;
+        let n = self.header.node_quantity;
+        if from == 0 || to == 0 || from > n || to > n {
+            return Err(format!("line {line}: edge ({from}, {to}) out of range 1..={n}"));
+        }
+        Ok(Some((from, to)))
+    }
+
+    //calls `f` for every edge, `progress` every PROGRESS_STEP edges and once at the end. Stops at the first
+    //error of `f`, and fails when the file does not hold as many edges as its header says
+    pub fn for_each_edge<F, P>(&mut self, pass: u8, mut f: F, progress: &mut P) -> Result<usize, String>
+    where F: FnMut(usize, usize) -> Result<(), String>, P: FnMut(Progress) {
+        let mut edges_read = 0;
+        while let Some((from, to)) = self.next_edge()? {
+            f(from, to).map_err(|e| format!("line {}: {e}", self.line))?;
+            edges_read += 1;
+            if edges_read % PROGRESS_STEP == 0 {
+                progress(Progress { pass, edges_read, edge_quantity: self.header.edge_quantity });
+            }
+        }
+        progress(Progress { pass, edges_read, edge_quantity: self.header.edge_quantity });
+        if edges_read != self.header.edge_quantity {
+            return Err(format!("header declares {} edges, file has {edges_read}", self.header.edge_quantity));
+        }
+        Ok(edges_read)
+    }
+
+    fn peek_non_whitespace(&mut self) -> Result<Option<u8>, String> {
+        loop {
+            let buffer = self.reader.fill_buf().map_err(|e| e.to_string())?;
+            if buffer.is_empty() {
+                return Ok(None);
+            }
+            let mut skipped = 0;
+            for &byte in buffer {
+                if !byte.is_ascii_whitespace() {
+                    self.reader.consume(skipped);
+                    return Ok(Some(byte));
+                }
+                if byte == b'\n' {
+                    self.line += 1;
+                }
+                skipped += 1;
+            }
+            self.reader.consume(skipped);
+        }
+    }
+
+    fn read_number(&mut self) -> Result<Option<usize>, String> {
+        match self.peek_non_whitespace()? {
+            None => return Ok(None),
+            Some(byte) if !byte.is_ascii_digit() => {
+                return Err(format!("line {}: unexpected {:?}", self.line, byte as char));
+            },
+            _ => {}
+        }
+        let mut number: usize = 0;
+        loop {
+            let buffer = self.reader.fill_buf().map_err(|e| e.to_string())?;
+            if buffer.is_empty() {
+                return Ok(Some(number));
+            }
+            let digits = buffer.iter().take_while(|byte| byte.is_ascii_digit()).count();
+            for &digit in &buffer[..digits] {
+                number = number
+                    .checked_mul(10)
+                    .and_then(|x| x.checked_add((digit - b'0') as usize))
+                    .ok_or(format!("line {}: number too large", self.line))?;
+            }
+            let done = digits < buffer.len();
+            self.reader.consume(digits);
+            if done {
+                return Ok(Some(number));
+            }
+        }
+    }
+}
+
+//Compressed sparse rows, neighbours of node v (1-based) are targets[offsets[v - 1]..offsets[v]]
+#[derive(Debug, PartialEq, Clone)]
+pub struct Csr {
+    pub directionality: Directionality,
+    pub offsets: Vec<usize>,
+    pub targets: Vec<u32>,
+}
+
+impl Csr {
+    //edges given to `Graph::new`, both ends of an undirected edge get the other as a neighbour
+    pub(crate) fn from_edges(directionality: Directionality, node_quantity: usize, edges: &[(usize, usize)]) -> Self {
+        let undirected = directionality == Directionality::Undirected;
+        let mut degrees: Vec<usize> = vec![0; node_quantity];
+        for &(from, to) in edges {
+            count_edge(&mut degrees, undirected, from, to);
+        }
+        let mut builder = CsrBuilder::new(directionality, &degrees).expect("node count above u32::MAX");
+        for &(from, to) in edges {
+            builder.add_edge(from, to).expect("edge counted in the degrees");
+        }
+        builder.finish().expect("every counted edge placed")
+    }
+
+    pub fn node_quantity(&self) -> usize {
+        self.offsets.len() - 1
+    }
+
+    pub fn neighbours(&self, node: usize) -> &[u32] {
+        &self.targets[self.offsets[node - 1]..self.offsets[node]]
+    }
+
+    //every edge turned around, an undirected graph is its own transpose
+    pub(crate) fn transpose(&self) -> Csr {
+        let mut degrees: Vec<usize> = vec![0; self.node_quantity()];
+        for &to in &self.targets {
+            degrees[to as usize - 1] += 1;
+        }
+        let mut builder = CsrBuilder::new(self.directionality.clone(), &degrees).expect("node count checked when built");
+        for from in 1..=self.node_quantity() {
+            for &to in self.neighbours(from) {
+                builder.place(to as usize, from).expect("edge counted in the degrees");
+            }
+        }
+        builder.finish().expect("every counted edge placed")
+    }
+}
+
+//Csr with exactly as many slots per node as its degree, filled one edge end at a time
+struct CsrBuilder {
+    csr: Csr,
+    //next free slot of every node
+    next: Vec<usize>,
+}
+
+impl CsrBuilder {
+    fn new(directionality: Directionality, degrees: &[usize]) -> Result<Self, String> {
+        let n = degrees.len();
+        if n > u32::MAX as usize {
+            return Err(format!("{n} nodes do not fit in u32"));
+        }
+        let mut offsets: Vec<usize> = Vec::with_capacity(n + 1);
+        offsets.push(0);
+        for degree in degrees {
+            offsets.push(offsets[offsets.len() - 1] + degree);
+        }
+        let next = offsets[..n].to_vec();
+        let targets: Vec<u32> = vec![0; offsets[n]];
+        Ok(CsrBuilder { csr: Csr { directionality, offsets, targets }, next })
+    }
+
+    //only the slots the degrees counted may be filled, and only with nodes they counted
+    fn place(&mut self, node: usize, neighbour: usize) -> Result<(), String> {
+        let n = self.next.len();
+        if node > n || neighbour > n || self.next[node - 1] == self.csr.offsets[node] {
+            return Err(CHANGED.to_string());
+        }
+        self.csr.targets[self.next[node - 1]] = neighbour as u32;
+        self.next[node - 1] += 1;
+        Ok(())
+    }
+
+    fn add_edge(&mut self, from: usize, to: usize) -> Result<(), String> {
+        self.place(from, to)?;
+        if self.csr.directionality == Directionality::Undirected {
+            self.place(to, from)?;
+        }
+        Ok(())
+    }
+
+    fn finish(self) -> Result<Csr, String> {
+        if self.next[..] != self.csr.offsets[1..] {
+            return Err(CHANGED.to_string());
+        }
+        Ok(self.csr)
+    }
+}
+
+fn count_edge(degrees: &mut [usize], undirected: bool, from: usize, to: usize) {
+    degrees[from - 1] += 1;
+    if undirected {
+        degrees[to - 1] += 1;
+    }
+}
+
+//Pass 1 counts the degrees, pass 2 rereads the file and writes every edge straight into place.
+//Peak memory is the adjacency itself, the edge list is never held
+pub fn load_csr<P, F>(path: P, mut progress: F) -> Result<Csr, String>
+where P: AsRef<Path>, F: FnMut(Progress) {
+    let degrees = count_degrees(&mut EdgeListReader::open(&path)?, &mut progress)?;
+    fill_csr(&mut EdgeListReader::open(&path)?, &degrees, &mut progress)
+}
+
+//The same two passes into a `Graph`, whose adjacency is that CSR
+pub fn load_graph<P, F>(path: P, progress: F) -> Result<Graph, String>
+where P: AsRef<Path>, F: FnMut(Progress) {
+    load_csr(path, progress).map(Graph::from_csr)
+}
+
+fn count_degrees<R, F>(reader: &mut EdgeListReader<R>, progress: &mut F) -> Result<Vec<usize>, String>
+where R: BufRead, F: FnMut(Progress) {
+    let undirected = reader.header().directionality == Directionality::Undirected;
+    let mut degrees: Vec<usize> = vec![0; reader.header().node_quantity];
+    reader.for_each_edge(1, |from, to| {
+        count_edge(&mut degrees, undirected, from, to);
+        Ok(())
+    }, progress)?;
+    Ok(degrees)
+}
+
+//second pass, the reader has to give the edges the degrees were counted from
+fn fill_csr<R, F>(reader: &mut EdgeListReader<R>, degrees: &[usize], progress: &mut F) -> Result<Csr, String>
+where R: BufRead, F: FnMut(Progress) {
+    let mut builder = CsrBuilder::new(reader.header().directionality.clone(), degrees)?;
+    reader.for_each_edge(2, |from, to| builder.add_edge(from, to), progress)?;
+    builder.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use crate::stream::*;
+
+    #[test]
+    fn edge_list_reader_test() {
+        let mut reader = EdgeListReader::new(Cursor::new("U\n4\n3\n1 2\n2 3\r\n4   1")).unwrap();
+        assert_eq!(reader.header(), &EdgeListHeader { directionality: Directionality::Undirected, node_quantity: 4, edge_quantity: 3 });
+        let mut edges: Vec<(usize, usize)> = Vec::new();
+        let read = reader.for_each_edge(1, |from, to| {
+            edges.push((from, to));
+            Ok(())
+        }, &mut |_| {}).unwrap();
+        assert_eq!(read, 3);
+        assert_eq!(edges, vec![(1, 2), (2, 3), (4, 1)]);
+    }
+
+    #[test]
+    fn edge_list_reader_small_buffer_test() {
+        let input = "D\n123\n2\n123 2\n3 1\n";
+        let mut reader = EdgeListReader::new(BufReader::with_capacity(1, Cursor::new(input))).unwrap();
+        assert_eq!(reader.next_edge(), Ok(Some((123, 2))));
+        assert_eq!(reader.next_edge(), Ok(Some((3, 1))));
+        assert_eq!(reader.next_edge(), Ok(None));
+    }
+
+    #[test]
+    fn edge_list_reader_errors_test() {
+        assert!(EdgeListReader::new(Cursor::new("X\n3\n0\n")).is_err());
+        let mut reader = EdgeListReader::new(Cursor::new("D\n3\n2\n1 2\n1 4\n")).unwrap();
+        assert_eq!(reader.next_edge(), Ok(Some((1, 2))));
+        assert_eq!(reader.next_edge(), Err("line 5: edge (1, 4) out of range 1..=3".to_string()));
+        let mut reader = EdgeListReader::new(Cursor::new("D\n3\n1\n1 x\n")).unwrap();
+        assert!(reader.next_edge().is_err());
+    }
+
+    #[test]
+    fn load_test() {
+        let path = std::env::temp_dir().join(format!("lista_1_stream_{}.txt", std::process::id()));
+        std::fs::write(&path, "U\n4\n3\n1 2\n2 3\n3 1\n").unwrap();
+        let mut passes: Vec<u8> = Vec::new();
+        let csr = load_csr(&path, |progress| passes.push(progress.pass)).unwrap();
+        let graph = load_graph(&path, |_| {}).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(passes, vec![1, 2]);
+        assert_eq!(csr.node_quantity(), 4);
+        assert_eq!(csr.neighbours(1), &[2, 3]);
+        assert_eq!(csr.neighbours(4), &[] as &[u32]);
+        assert_eq!(graph.edges(), vec![(1, 2), (1, 3), (2, 3)]);
+        assert!(!graph.is_bipartite());
+    }
+
+    #[test]
+    fn load_errors_test() {
+        let path = std::env::temp_dir().join(format!("lista_1_stream_errors_{}.txt", std::process::id()));
+        std::fs::write(&path, "D\n3\n3\n1 2\n2 3\n").unwrap();
+        assert_eq!(load_csr(&path, |_| {}), Err("header declares 3 edges, file has 2".to_string()));
+        std::fs::remove_file(&path).unwrap();
+        //the file as another process rewrote it between the passes
+        let second_pass = |degrees: &[usize]| {
+            let mut reader = EdgeListReader::new(Cursor::new("D\n3\n2\n1 2\n1 3\n")).unwrap();
+            fill_csr(&mut reader, degrees, &mut |_| {})
+        };
+        assert_eq!(second_pass(&[1, 1, 0]), Err(format!("line 5: {CHANGED}")));
+        assert_eq!(second_pass(&[3, 0, 0]), Err(CHANGED.to_string()));
+        assert_eq!(second_pass(&[2, 0]), Err(format!("line 5: {CHANGED}")));
+        assert_eq!(second_pass(&[2, 0, 0]).unwrap().neighbours(1), &[2, 3]);
+    }
+}
@@ -1,12 +1,19 @@
+import sys
+
 import pandas as pd
 import seaborn as sns
 import matplotlib.pyplot as plt
 
-df = pd.read_csv("dijkstra_data.csv", header=0, sep=";")
-print(df.value_counts("n"))
-print(df.head())
-df = df.astype({'n': 'int', "time": "float"})
+# one row per algorithm, graph and challenge as written by the Test mode or `lista_3 bench`
+path = sys.argv[1] if len(sys.argv) > 1 else "dijkstra_data.csv"
+df = pd.read_csv(path, header=0, sep=";")
+df = df.astype({'n': 'int', "mean": "float", "stddev": "float"})
+df["kind"] = df["challenge"].str.rsplit(".", n=1).str[-1]
+print(df[["algo", "graph", "challenge", "median", "mean", "stddev", "scanned_nodes", "relaxations"]])
 
 sns.set_style("darkgrid")
-sns.barplot(data=df, x="n", y="time", hue="algo")
-plt.savefig("ok.png")
+for kind, group in df.groupby("kind"):
+    plt.figure()
+    ax = sns.barplot(data=group, x="n", y="mean", hue="algo")
+    ax.set_title(kind)
+    plt.savefig("ok.png" if kind == "ss" else f"ok_{kind}.png")
@@ -0,0 +1,179 @@
+use crate::astar::P2pSearch;
+use crate::stats::{self, NoStats, Stats};
+use crate::Graph;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LandmarkSelection {
+    //every next landmark is the node farthest from the ones already chosen
+    Farthest,
+    //leaf of the shortest path subtree whose lower bounds are the worst, see Goldberg and Werneck
+    Avoid,
+}
+
+//Distances from and to a few landmarks, by the triangle inequality d(v, t) >= d(L, t) - d(L, v)
+//and d(v, t) >= d(v, L) - d(t, L) for every landmark L, which gives A* a consistent estimate without coordinates
+#[derive(Clone, Debug, PartialEq)]
+pub struct Landmarks {
+    landmarks: Vec<usize>,
+    //from[i][v] = d(landmark i, v + 1)
+    from: Vec<Vec<Option<usize>>>,
+    //to[i][v] = d(v + 1, landmark i)
+    to: Vec<Vec<Option<usize>>>,
+}
+
+impl Landmarks {
+    pub fn new(graph: &Graph, quantity: usize, selection: LandmarkSelection) -> Self {
+        let reversed = graph.reversed();
+        let mut landmarks = Landmarks { landmarks: Vec::new(), from: Vec::new(), to: Vec::new() };
+        let quantity = quantity.min(graph.node_quantity);
+        while landmarks.landmarks.len() < quantity {
+            let landmark = match selection {
+                LandmarkSelection::Farthest => landmarks.farthest(graph),
+                LandmarkSelection::Avoid => landmarks.avoid(graph).unwrap_or_else(|| landmarks.farthest(graph)),
+            };
+            landmarks.landmarks.push(landmark);
+            landmarks.from.push(graph.djikstra_classic_ss(landmark));
+            landmarks.to.push(reversed.djikstra_classic_ss(landmark));
+        }
+        landmarks
+    }
+
+    pub fn landmarks(&self) -> &[usize] {
+        &self.landmarks
+    }
+
+    //lower bound on the cost from node to goal, both 0-based
+    pub fn lower_bound(&self, node: usize, goal: usize) -> usize {
+        let mut bound = 0;
+        for (from, to) in self.from.iter().zip(&self.to) {
+            if let (Some(landmark_node), Some(landmark_goal)) = (from[node], from[goal]) {
+                bound = bound.max(landmark_goal.saturating_sub(landmark_node));
+            }
+            if let (Some(node_landmark), Some(goal_landmark)) = (to[node], to[goal]) {
+                bound = bound.max(node_landmark.saturating_sub(goal_landmark));
+            }
+        }
+        bound
+    }
+
+    //node with the largest distance to its closest landmark, nodes some landmark cannot reach count as
+    //infinitely far. Starts from the node farthest from node 1
+    fn farthest(&self, graph: &Graph) -> usize {
+        let start;
+        let tables = if self.from.is_empty() {
+            start = [graph.djikstra_classic_ss(1)];
+            &start[..]
+        } else {
+            &self.from[..]
+        };
+        let mut farthest: Option<(usize, usize)> = None;
+        for node in 0..graph.node_quantity {
+            if self.landmarks.contains(&(node + 1)) {
+                continue;
+            }
+            let distance = tables.iter().map(|table| table[node].unwrap_or(usize::MAX)).min().unwrap();
+            if farthest.is_none_or(|(longest, _)| distance > longest) {
+                farthest = Some((distance, node + 1));
+            }
+        }
+        //fewer landmarks than nodes, so some node is left
+        farthest.unwrap().1
+    }
+
+    //In the shortest path tree of a root every node weighs d(root, v) minus its current lower bound and a
+    //subtree is as heavy as its nodes, or 0 if it holds a landmark. Walks from the heaviest node down to a
+    //leaf, always into the heaviest child. None when every subtree already has a landmark
+    fn avoid(&self, graph: &Graph) -> Option<usize> {
+        //any node works as the root, a fixed stride keeps runs reproducible
+        let root = (self.landmarks.len() * 7919) % graph.node_quantity + 1;
+        let tree = graph.djikstra_classic_tree(root);
+        let mut children: Vec<Vec<usize>> = vec![Vec::new(); graph.node_quantity];
+        for (node, predecessor) in tree.predecessors.iter().enumerate() {
+            if let Some(predecessor) = predecessor {
+                children[predecessor - 1].push(node);
+            }
+        }
+        //post order, children before their parent
+        let mut order = Vec::new();
+        let mut stack = vec![root - 1];
+        while let Some(node) = stack.pop() {
+            order.push(node);
+            stack.extend(&children[node]);
+        }
+        let mut size = vec![0; graph.node_quantity];
+        let mut has_landmark = vec![false; graph.node_quantity];
+        for &node in order.iter().rev() {
+            has_landmark[node] = self.landmarks.contains(&(node + 1)) || children[node].iter().any(|child| has_landmark[*child]);
+            if !has_landmark[node] {
+                let weight = tree.distances[node].unwrap() - self.lower_bound(root - 1, node);
+                size[node] = weight + children[node].iter().map(|child| size[*child]).sum::<usize>();
+            }
+        }
+        let mut node = (0..graph.node_quantity).max_by_key(|node| (size[*node], usize::MAX - node))?;
+        if size[node] == 0 {
+            return None;
+        }
+        while let Some(child) = children[node].iter().copied().max_by_key(|child| size[*child]) {
+            node = child;
+        }
+        Some(node + 1)
+    }
+}
+
+impl Graph {
+    pub fn alt_p2p(&self, landmarks: &Landmarks, start: usize, goal: usize) -> P2pSearch {
+        self.goal_directed_p2p(start, goal, |node| landmarks.lower_bound(node, goal - 1), &mut NoStats)
+    }
+
+    pub fn alt_p2p_stats<S: Stats>(&self, landmarks: &Landmarks, start: usize, goal: usize) -> (P2pSearch, S) {
+        stats::collect(|stats| self.goal_directed_p2p(start, goal, |node| landmarks.lower_bound(node, goal - 1), stats))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::alt::*;
+    use crate::random_graph;
+
+    #[test]
+    fn landmarks_test() {
+        let mut graph = Graph::new(5);
+        for node in 1..5 {
+            graph.add_edge(node, node + 1, 2);
+            graph.add_edge(node + 1, node, 3);
+        }
+        let landmarks = Landmarks::new(&graph, 2, LandmarkSelection::Farthest);
+        assert_eq!(landmarks.landmarks(), &[5, 1]);
+        //d(1, 5) = 8 and d(5, 1) = 12 are exact through the landmarks at the ends of the line
+        assert_eq!(landmarks.lower_bound(0, 4), 8);
+        assert_eq!(landmarks.lower_bound(4, 0), 12);
+        assert_eq!(landmarks.lower_bound(1, 3), 4);
+        let avoid = Landmarks::new(&graph, 5, LandmarkSelection::Avoid);
+        let mut chosen = avoid.landmarks().to_vec();
+        chosen.sort();
+        assert_eq!(chosen, vec![1, 2, 3, 4, 5]);
+    }
+
+    #[test]
+    fn alt_random_test() {
+        for seed in 1..25 {
+            let graph = random_graph(60, 200, 20, seed);
+            for selection in [LandmarkSelection::Farthest, LandmarkSelection::Avoid] {
+                let landmarks = Landmarks::new(&graph, 4, selection);
+                let (mut alt_settled, mut dijkstra_settled) = (0, 0);
+                for start in [1, 22, 60] {
+                    let tree = graph.djikstra_classic_tree(start);
+                    for goal in 1..=60 {
+                        let bound = landmarks.lower_bound(start - 1, goal - 1);
+                        assert!(tree.distance(goal).is_none_or(|distance| bound <= distance));
+                        let alt = graph.alt_p2p(&landmarks, start, goal);
+                        assert_eq!(alt.distance, tree.distance(goal));
+                        alt_settled += alt.settled;
+                        dijkstra_settled += graph.djikstra_classic_p2p_settled(start, goal).settled;
+                    }
+                }
+                assert!(alt_settled <= dijkstra_settled);
+            }
+        }
+    }
+}
@@ -0,0 +1,280 @@
+use std::io::{self, Write};
+
+use rayon::prelude::*;
+
+use crate::dimacs::DimacsError;
+use crate::signed::{NegativeCycle, SignedGraph};
+use crate::Graph;
+
+//Distances between every pair of nodes, row by row, distance(from, to) takes node ids
+#[derive(Clone, Debug, PartialEq)]
+pub struct DistanceMatrix {
+    node_quantity: usize,
+    distances: Vec<Option<i64>>,
+}
+
+impl DistanceMatrix {
+    pub fn node_quantity(&self) -> usize {
+        self.node_quantity
+    }
+
+    pub fn distance(&self, from: usize, to: usize) -> Option<i64> {
+        self.distances[(from - 1) * self.node_quantity + to - 1]
+    }
+
+    //distances from one node, indexed by node - 1
+    pub fn row(&self, from: usize) -> &[Option<i64>] {
+        &self.distances[(from - 1) * self.node_quantity..from * self.node_quantity]
+    }
+
+    pub fn write<W: Write>(&self, out: &mut W) -> io::Result<()> {
+        write_matrix_header(out, self.node_quantity)?;
+        for from in 1..=self.node_quantity {
+            write_row(out, from, self.row(from))?;
+        }
+        Ok(())
+    }
+}
+
+//Matrix file: p apsp <n> followed by n lines r <source> <d1> ... <dn>, x for unreachable nodes
+pub fn write_matrix_header<W: Write>(out: &mut W, node_quantity: usize) -> io::Result<()> {
+    writeln!(out, "p apsp {node_quantity}")
+}
+
+pub fn write_row<W: Write>(out: &mut W, source: usize, row: &[Option<i64>]) -> io::Result<()> {
+    write!(out, "r {source}")?;
+    for distance in row {
+        match distance {
+            Some(distance) => write!(out, " {distance}")?,
+            None => write!(out, " x")?,
+        }
+    }
+    writeln!(out)
+}
+
+//Floyd-Warshall over a dense n x n table, O(n^3) time and O(n^2) memory, meant for small graphs
+#[derive(Clone, Debug, PartialEq)]
+pub struct FloydWarshall {
+    matrix: DistanceMatrix,
+    //next[from * n + to] = node after from on a shortest path to to, 0-based
+    next: Vec<Option<usize>>,
+}
+
+impl FloydWarshall {
+    pub fn new(graph: &SignedGraph) -> Result<Self, NegativeCycle> {
+        let n = graph.node_quantity();
+        let mut distances = vec![None; n * n];
+        let mut next = vec![None; n * n];
+        for from in 0..n {
+            distances[from * n + from] = Some(0);
+            next[from * n + from] = Some(from);
+            for &(to, cost) in graph.arcs(from) {
+                if distances[from * n + to].is_none_or(|old| cost < old) {
+                    distances[from * n + to] = Some(cost);
+                    next[from * n + to] = Some(to);
+                }
+            }
+        }
+        for middle in 0..n {
+            for from in 0..n {
+                let Some(to_middle) = distances[from * n + middle] else {
+                    continue;
+                };
+                for to in 0..n {
+                    let Some(from_middle) = distances[middle * n + to] else {
+                        continue;
+                    };
+                    //labels around a negative cycle fall fast, saturating keeps them from wrapping
+                    let through = to_middle.saturating_add(from_middle);
+                    if distances[from * n + to].is_none_or(|old| through < old) {
+                        distances[from * n + to] = Some(through);
+                        next[from * n + to] = next[from * n + middle];
+                    }
+                }
+            }
+            //a node below zero to itself lies on a negative cycle, Bellman-Ford from it finds one
+            if let Some(node) = (0..n).find(|node| distances[node * n + node] < Some(0)) {
+                return Err(graph.bellman_ford(node + 1).expect_err("the node reaches a negative cycle"));
+            }
+        }
+        Ok(FloydWarshall { matrix: DistanceMatrix { node_quantity: n, distances }, next })
+    }
+
+    pub fn matrix(&self) -> &DistanceMatrix {
+        &self.matrix
+    }
+
+    pub fn distance(&self, from: usize, to: usize) -> Option<i64> {
+        self.matrix.distance(from, to)
+    }
+
+    //nodes from from to to, None if to is unreachable
+    pub fn path(&self, from: usize, to: usize) -> Option<Vec<usize>> {
+        let n = self.matrix.node_quantity;
+        let mut node = from - 1;
+        let mut path = vec![from];
+        while node != to - 1 {
+            node = self.next[node * n + to - 1]?;
+            path.push(node + 1);
+        }
+        Some(path)
+    }
+}
+
+//Johnson: potentials from Bellman-Ford make every reduced cost w(u, v) + h(u) - h(v) non negative, then
+//Dijkstra runs from every source on the reweighted graph, in parallel, and d(u, v) = d'(u, v) - h(u) + h(v)
+//Johnson fails on a negative cycle, or when a reduced cost does not fit the u32 costs of Graph
+#[derive(Debug)]
+pub enum JohnsonError {
+    NegativeCycle(NegativeCycle),
+    TooLarge(DimacsError),
+}
+
+impl From<NegativeCycle> for JohnsonError {
+    fn from(cycle: NegativeCycle) -> Self {
+        JohnsonError::NegativeCycle(cycle)
+    }
+}
+
+impl From<DimacsError> for JohnsonError {
+    fn from(error: DimacsError) -> Self {
+        JohnsonError::TooLarge(error)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Johnson {
+    reweighted: Graph,
+    potentials: Vec<i64>,
+}
+
+impl Johnson {
+    //the potentials are distances from a virtual node with a zero cost arc to every node
+    pub fn new(graph: &SignedGraph) -> Result<Self, JohnsonError> {
+        let n = graph.node_quantity();
+        let mut extended = graph.clone();
+        extended.add_node();
+        for node in 1..=n {
+            extended.add_edge(n + 1, node, 0);
+        }
+        let tree = extended.bellman_ford(n + 1)?;
+        let potentials: Vec<i64> = tree.distances[..n].iter().map(|distance| distance.unwrap()).collect();
+        let mut arcs = Vec::with_capacity(graph.arc_quantity());
+        for from in 0..n {
+            for &(to, cost) in graph.arcs(from) {
+                arcs.push((from + 1, to + 1, (cost + potentials[from] - potentials[to]) as usize));
+            }
+        }
+        let reweighted = Graph::from_arcs(n, &arcs)?;
+        Ok(Johnson { reweighted, potentials })
+    }
+
+    pub fn node_quantity(&self) -> usize {
+        self.potentials.len()
+    }
+
+    //distances from one source, indexed by node - 1
+    pub fn row(&self, source: usize) -> Vec<Option<i64>> {
+        self.reweighted
+            .djikstra_classic_ss(source)
+            .into_iter()
+            .zip(&self.potentials)
+            .map(|(distance, potential)| distance.map(|distance| distance as i64 - self.potentials[source - 1] + potential))
+            .collect()
+    }
+
+    pub fn matrix(&self) -> DistanceMatrix {
+        let distances = (1..=self.node_quantity()).into_par_iter().flat_map_iter(|source| self.row(source)).collect();
+        DistanceMatrix { node_quantity: self.node_quantity(), distances }
+    }
+
+    //Writes the matrix file row by row. Rows are computed in parallel a batch at a time, so only one batch
+    //is held in memory however large the graph
+    pub fn write<W: Write>(&self, out: &mut W) -> io::Result<()> {
+        let n = self.node_quantity();
+        let batch = rayon::current_num_threads() * 16;
+        write_matrix_header(out, n)?;
+        for first in (1..=n).step_by(batch) {
+            let rows: Vec<Vec<Option<i64>>> = (first..(first + batch).min(n + 1)).into_par_iter().map(|source| self.row(source)).collect();
+            for (source, row) in (first..).zip(&rows) {
+                write_row(out, source, row)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::apsp::*;
+    use crate::random_graph;
+
+    //random potentials keep every cycle as expensive as before while making some arcs negative
+    fn negative_arcs(seed: u64) -> SignedGraph {
+        let graph = SignedGraph::from(&random_graph(30, 100, 20, seed));
+        let potential = |node: usize| ((node * 37 + seed as usize) % 15) as i64;
+        let mut shifted = SignedGraph::new(30);
+        for from in 0..30 {
+            for &(to, cost) in graph.arcs(from) {
+                shifted.add_edge(from + 1, to + 1, cost + potential(from) - potential(to));
+            }
+        }
+        shifted
+    }
+
+    #[test]
+    fn floyd_warshall_test() {
+        let mut graph = SignedGraph::new(4);
+        graph.add_edge(1, 2, 3);
+        graph.add_edge(2, 3, -2);
+        graph.add_edge(1, 3, 4);
+        graph.add_edge(3, 4, 2);
+        let floyd_warshall = FloydWarshall::new(&graph).unwrap();
+        assert_eq!(floyd_warshall.distance(1, 4), Some(3));
+        assert_eq!(floyd_warshall.distance(4, 1), None);
+        assert_eq!(floyd_warshall.path(1, 4), Some(vec![1, 2, 3, 4]));
+        assert_eq!(floyd_warshall.path(2, 2), Some(vec![2]));
+        assert_eq!(floyd_warshall.path(4, 1), None);
+        let mut out: Vec<u8> = Vec::new();
+        floyd_warshall.matrix().write(&mut out).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), "p apsp 4\nr 1 0 3 1 3\nr 2 x 0 -2 0\nr 3 x x 0 2\nr 4 x x x 0\n");
+        graph.add_edge(3, 1, -2);
+        let cycle = FloydWarshall::new(&graph).unwrap_err();
+        assert!(cycle.cost < 0);
+        assert!(matches!(Johnson::new(&graph), Err(JohnsonError::NegativeCycle(_))));
+        let mut oversized = SignedGraph::new(2);
+        oversized.add_edge(1, 2, 5_000_000_000);
+        assert!(matches!(Johnson::new(&oversized), Err(JohnsonError::TooLarge(DimacsError::TooLarge { what: "arc cost", .. }))));
+        assert_eq!(FloydWarshall::new(&oversized).unwrap().distance(1, 2), Some(5_000_000_000));
+    }
+
+    #[test]
+    fn apsp_random_test() {
+        for seed in 1..15 {
+            let graph = negative_arcs(seed);
+            let floyd_warshall = FloydWarshall::new(&graph).unwrap();
+            let johnson = Johnson::new(&graph).unwrap();
+            assert_eq!(&johnson.matrix(), floyd_warshall.matrix());
+            for from in 1..=30 {
+                let tree = graph.bellman_ford(from).unwrap();
+                assert_eq!(floyd_warshall.matrix().row(from), &tree.distances[..]);
+                for to in 1..=30 {
+                    //the reconstructed path costs what the table says
+                    let Some(path) = floyd_warshall.path(from, to) else {
+                        assert_eq!(tree.distance(to), None);
+                        continue;
+                    };
+                    let cost: i64 = path
+                        .windows(2)
+                        .map(|arc| graph.arcs(arc[0] - 1).iter().filter(|(to, _)| *to == arc[1] - 1).map(|(_, cost)| *cost).min().unwrap())
+                        .sum();
+                    assert_eq!(Some(cost), tree.distance(to));
+                }
+            }
+            let (mut streamed, mut written): (Vec<u8>, Vec<u8>) = (Vec::new(), Vec::new());
+            johnson.write(&mut streamed).unwrap();
+            floyd_warshall.matrix().write(&mut written).unwrap();
+            assert_eq!(streamed, written);
+        }
+    }
+}
@@ -0,0 +1,173 @@
+use std::collections::BinaryHeap;
+
+use crate::dimacs::{Coordinates, DimacsError};
+use crate::stats::{self, Event, NoStats, Stats};
+use crate::{Graph, SearchNode};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct P2pSearch {
+    pub distance: Option<usize>,
+    //nodes taken off the queue before the goal was reached
+    pub settled: usize,
+}
+
+//Straight line distance to the goal scaled down to the cheapest cost per unit of length in the graph.
+//For travel time graphs that is 1 / maximum speed, so the estimate never exceeds the remaining cost
+//and h(u) <= w(u, v) + h(v) holds on every arc
+#[derive(Clone, Debug)]
+pub struct GeometricHeuristic {
+    coordinates: Vec<(i64, i64)>,
+    scale: f64,
+}
+
+impl GeometricHeuristic {
+    pub fn new(graph: &Graph, coordinates: &Coordinates) -> Result<Self, DimacsError> {
+        if coordinates.coordinates.len() != graph.node_quantity {
+            return Err(DimacsError::CountMismatch {
+                what: "coordinates",
+                expected: graph.node_quantity,
+                found: coordinates.coordinates.len(),
+            });
+        }
+        let coordinates = coordinates.coordinates.clone();
+        let mut scale = f64::INFINITY;
+        for from in 0..graph.node_quantity {
+            for (to, cost) in graph.arcs(from) {
+                let length = euclidean(coordinates[from], coordinates[to]);
+                if length > 0.0 {
+                    scale = scale.min(cost as f64 / length);
+                }
+            }
+        }
+        if !scale.is_finite() {
+            scale = 0.0;
+        }
+        //keep rounding errors from making the estimate inconsistent
+        scale *= 1.0 - 1e-9;
+        Ok(GeometricHeuristic { coordinates, scale })
+    }
+
+    pub fn scale(&self) -> f64 {
+        self.scale
+    }
+
+    //lower bound on the cost from node to goal, both 0-based
+    pub fn estimate(&self, node: usize, goal: usize) -> usize {
+        (self.scale * euclidean(self.coordinates[node], self.coordinates[goal])).floor() as usize
+    }
+}
+
+fn euclidean(a: (i64, i64), b: (i64, i64)) -> f64 {
+    let dx = (a.0 - b.0) as f64;
+    let dy = (a.1 - b.1) as f64;
+    (dx * dx + dy * dy).sqrt()
+}
+
+impl Graph {
+    pub fn astar_p2p(&self, heuristic: &GeometricHeuristic, start: usize, goal: usize) -> P2pSearch {
+        self.goal_directed_p2p(start, goal, |node| heuristic.estimate(node, goal - 1), &mut NoStats)
+    }
+
+    pub fn astar_p2p_stats<S: Stats>(&self, heuristic: &GeometricHeuristic, start: usize, goal: usize) -> (P2pSearch, S) {
+        stats::collect(|stats| self.goal_directed_p2p(start, goal, |node| heuristic.estimate(node, goal - 1), stats))
+    }
+
+    //plain Dijkstra through the same loop, for comparing the settled counts
+    pub fn djikstra_classic_p2p_settled(&self, start: usize, goal: usize) -> P2pSearch {
+        self.goal_directed_p2p(start, goal, |_| 0, &mut NoStats)
+    }
+
+    //A* for any consistent estimate, nodes are settled once like in Dijkstra
+    pub(crate) fn goal_directed_p2p<H: Fn(usize) -> usize, S: Stats>(&self, start: usize, goal: usize, estimate: H, stats: &mut S) -> P2pSearch {
+        let (start, goal) = (start - 1, goal - 1);
+        let mut distances: Vec<Option<usize>> = vec![None; self.node_quantity];
+        let mut settled = vec![false; self.node_quantity];
+        let mut settled_quantity = 0;
+        let mut queue: BinaryHeap<SearchNode> = BinaryHeap::new();
+        distances[start] = Some(0);
+        queue.push(SearchNode { id: start, distance: estimate(start) });
+        stats.record(Event::Push);
+        while let Some(SearchNode { id: node, .. }) = queue.pop() {
+            stats.record(Event::Pop);
+            if settled[node] {
+                continue;
+            }
+            settled[node] = true;
+            settled_quantity += 1;
+            let d = distances[node].unwrap();
+            if node == goal {
+                return P2pSearch { distance: Some(d), settled: settled_quantity };
+            }
+            stats.record(Event::ScannedNode);
+            for (to, cost) in self.arcs(node) {
+                stats.record(Event::ScannedArc);
+                let new_distance = d + cost;
+                if !settled[to] && distances[to].is_none_or(|old| new_distance < old) {
+                    distances[to] = Some(new_distance);
+                    queue.push(SearchNode { id: to, distance: new_distance + estimate(to) });
+                    stats.record(Event::Relaxation);
+                    stats.record(Event::Push);
+                }
+            }
+        }
+        P2pSearch { distance: None, settled: settled_quantity }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::astar::*;
+    use crate::stats::SearchStats;
+
+    //side x side grid, horizontal arcs cost 10 per unit, vertical 13, coordinates 1 unit apart
+    fn grid(side: usize) -> (Graph, Coordinates) {
+        let mut graph = Graph::new(side * side);
+        let id = |x: usize, y: usize| y * side + x + 1;
+        let mut coordinates = Vec::new();
+        for y in 0..side {
+            for x in 0..side {
+                coordinates.push((x as i64, y as i64));
+                if x + 1 < side {
+                    graph.add_edge(id(x, y), id(x + 1, y), 10);
+                    graph.add_edge(id(x + 1, y), id(x, y), 10);
+                }
+                if y + 1 < side {
+                    graph.add_edge(id(x, y), id(x, y + 1), 13);
+                    graph.add_edge(id(x, y + 1), id(x, y), 13);
+                }
+            }
+        }
+        (graph, Coordinates { coordinates })
+    }
+
+    #[test]
+    fn astar_test() {
+        let (graph, coordinates) = grid(20);
+        let heuristic = GeometricHeuristic::new(&graph, &coordinates).unwrap();
+        assert!(heuristic.scale() < 10.0 && heuristic.scale() > 9.99);
+        for (start, goal) in [(1, 400), (400, 1), (15, 386), (210, 210)] {
+            let astar = graph.astar_p2p(&heuristic, start, goal);
+            let dijkstra = graph.djikstra_classic_p2p_settled(start, goal);
+            assert_eq!(astar.distance, graph.djikstra_classic_p2p(start, goal));
+            assert_eq!(dijkstra.distance, astar.distance);
+            assert!(astar.settled <= dijkstra.settled);
+            //the goal is settled but not scanned, every entry pushed besides the start came from a relaxation
+            let (search, stats) = graph.astar_p2p_stats::<SearchStats>(&heuristic, start, goal);
+            assert_eq!(search, astar);
+            assert_eq!(stats.scanned_nodes, astar.settled - 1);
+            assert_eq!(stats.pushes, stats.relaxations + 1);
+            assert!(stats.pops <= stats.pushes && stats.pops >= astar.settled);
+        }
+        assert!(graph.astar_p2p(&heuristic, 1, 20).settled < graph.djikstra_classic_p2p_settled(1, 20).settled);
+    }
+
+    #[test]
+    fn astar_unreachable_test() {
+        let mut graph = Graph::new(3);
+        graph.add_edge(1, 2, 5);
+        let coordinates = Coordinates { coordinates: vec![(0, 0), (3, 4), (6, 8)] };
+        let heuristic = GeometricHeuristic::new(&graph, &coordinates).unwrap();
+        assert_eq!(graph.astar_p2p(&heuristic, 1, 3), P2pSearch { distance: None, settled: 2 });
+        assert!(GeometricHeuristic::new(&graph, &Coordinates { coordinates: vec![(0, 0)] }).is_err());
+    }
+}
@@ -0,0 +1,84 @@
+use std::time::{Duration, Instant};
+
+use rayon::prelude::*;
+use rayon::{ThreadPool, ThreadPoolBuildError, ThreadPoolBuilder};
+
+use crate::{Algorithm, Graph, ShortestPathTree};
+
+//answer of one query and how long it took on its own
+#[derive(Clone, Debug, PartialEq)]
+pub struct Timed<T> {
+    pub result: T,
+    pub time: Duration,
+}
+
+//Runs batches of queries over a shared graph on a fixed number of threads. Results come back in the
+//order of the queries, whichever thread answered them
+pub struct BatchRunner {
+    pool: ThreadPool,
+}
+
+impl BatchRunner {
+    //0 threads picks one per core
+    pub fn new(threads: usize) -> Result<Self, ThreadPoolBuildError> {
+        Ok(BatchRunner { pool: ThreadPoolBuilder::new().num_threads(threads).build()? })
+    }
+
+    pub fn threads(&self) -> usize {
+        self.pool.current_num_threads()
+    }
+
+    pub fn run<Q, T, F>(&self, queries: &[Q], answer: F) -> Vec<Timed<T>>
+    where
+        Q: Sync,
+        T: Send,
+        F: Fn(&Q) -> T + Sync,
+    {
+        self.pool.install(|| {
+            queries
+                .par_iter()
+                .map(|query| {
+                    let start = Instant::now();
+                    let result = answer(query);
+                    Timed { result, time: start.elapsed() }
+                })
+                .collect()
+        })
+    }
+
+    //distances of (start, goal) pairs
+    pub fn p2p(&self, graph: &Graph, algorithm: Algorithm, queries: &[(usize, usize)]) -> Vec<Timed<Option<usize>>> {
+        self.run(queries, |&(start, goal)| graph.p2p(algorithm, start, goal))
+    }
+
+    pub fn ss(&self, graph: &Graph, algorithm: Algorithm, sources: &[usize]) -> Vec<Timed<ShortestPathTree>> {
+        self.run(sources, |&src| graph.shortest_path_tree(algorithm, src))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::batch::*;
+    use crate::random_graph;
+
+    #[test]
+    fn batch_test() {
+        let graph = random_graph(100, 400, 50, 3);
+        let runner = BatchRunner::new(3).unwrap();
+        assert_eq!(runner.threads(), 3);
+        let queries: Vec<(usize, usize)> = (1..=100).map(|node| (node, 101 - node)).collect();
+        for algorithm in Algorithm::ALL {
+            let answers = runner.p2p(&graph, algorithm, &queries);
+            assert_eq!(answers.len(), queries.len());
+            for (answer, &(start, goal)) in answers.iter().zip(&queries) {
+                assert_eq!(answer.result, graph.djikstra_classic_p2p(start, goal));
+            }
+        }
+        let sources = [7, 1, 100, 7];
+        let trees = runner.ss(&graph, Algorithm::Dial, &sources);
+        for (tree, src) in trees.iter().zip(sources) {
+            assert_eq!(tree.result.source, src);
+            assert_eq!(tree.result.distances, graph.djikstra_classic_ss(src));
+        }
+    }
+}
@@ -0,0 +1,164 @@
+use std::io::{self, Write};
+use std::time::Instant;
+
+use crate::stats::SearchStats;
+
+pub const CSV_HEADER: &str = "algo;graph;challenge;n;e;queries;repetitions;min;median;mean;stddev;scanned_nodes;relaxations";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BenchOptions {
+    //timed runs of every query
+    pub repetitions: usize,
+    //untimed runs of the first queries before timing starts
+    pub warmup: usize,
+}
+
+//seconds over all timed runs of all queries
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Summary {
+    pub min: f64,
+    pub median: f64,
+    pub mean: f64,
+    //sample standard deviation, 0 for a single run
+    pub stddev: f64,
+}
+
+impl Summary {
+    pub fn new(samples: &[f64]) -> Self {
+        if samples.is_empty() {
+            return Summary::default();
+        }
+        let mut sorted = samples.to_vec();
+        sorted.sort_by(f64::total_cmp);
+        let len = sorted.len();
+        let median = if len % 2 == 1 { sorted[len / 2] } else { (sorted[len / 2 - 1] + sorted[len / 2]) / 2.0 };
+        let mean = sorted.iter().sum::<f64>() / len as f64;
+        let variance = if len > 1 { sorted.iter().map(|sample| (sample - mean).powi(2)).sum::<f64>() / (len - 1) as f64 } else { 0.0 };
+        Summary { min: sorted[0], median, mean, stddev: variance.sqrt() }
+    }
+}
+
+//one CSV row: an algorithm on the queries of one challenge of one graph
+#[derive(Clone, Debug, PartialEq)]
+pub struct Measurement {
+    pub algorithm: String,
+    pub graph: String,
+    pub challenge: String,
+    pub node_quantity: usize,
+    pub arc_quantity: usize,
+    pub queries: usize,
+    pub repetitions: usize,
+    pub summary: Summary,
+    //means per query, None for algorithms without counters
+    pub scanned_nodes: Option<f64>,
+    pub relaxations: Option<f64>,
+}
+
+impl Measurement {
+    //per query means of counters summed over one run of every query
+    pub fn set_counters(&mut self, total: Option<SearchStats>) {
+        let queries = self.queries.max(1) as f64;
+        self.scanned_nodes = total.map(|total| total.scanned_nodes as f64 / queries);
+        self.relaxations = total.map(|total| total.relaxations as f64 / queries);
+    }
+
+    pub fn write_csv<W: Write>(&self, out: &mut W) -> io::Result<()> {
+        let counter = |counter: Option<f64>| counter.map_or(String::new(), |counter| format!("{counter:.1}"));
+        writeln!(
+            out,
+            "{};{};{};{};{};{};{};{:.9};{:.9};{:.9};{:.9};{};{}",
+            self.algorithm,
+            self.graph,
+            self.challenge,
+            self.node_quantity,
+            self.arc_quantity,
+            self.queries,
+            self.repetitions,
+            self.summary.min,
+            self.summary.median,
+            self.summary.mean,
+            self.summary.stddev,
+            counter(self.scanned_nodes),
+            counter(self.relaxations),
+        )
+    }
+}
+
+//Runs query 0..queries through run, first warmup of them untimed and then every query repetitions times.
+//The timed runs should go through NoStats so that counting does not show up in the times
+pub fn measure<F: FnMut(usize)>(options: BenchOptions, queries: usize, mut run: F) -> Summary {
+    if queries == 0 {
+        return Summary::default();
+    }
+    for query in 0..options.warmup {
+        run(query % queries);
+    }
+    let mut samples = Vec::with_capacity(queries * options.repetitions);
+    for _ in 0..options.repetitions {
+        for query in 0..queries {
+            let start = Instant::now();
+            run(query);
+            samples.push(start.elapsed().as_secs_f64());
+        }
+    }
+    Summary::new(&samples)
+}
+
+//counters summed over one untimed run of every query, the searches do the same work on every repetition
+pub fn count<F: FnMut(usize) -> SearchStats>(queries: usize, mut run: F) -> SearchStats {
+    let mut total = SearchStats::default();
+    for query in 0..queries {
+        total.add(&run(query));
+    }
+    total
+}
+
+//file name without directories, whichever separator the path uses
+pub fn display_name(path: &str) -> String {
+    path.rsplit(['/', '\\']).next().unwrap_or(path).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bench::*;
+
+    #[test]
+    fn summary_test() {
+        let summary = Summary::new(&[4.0, 1.0, 3.0, 2.0]);
+        assert_eq!(summary.min, 1.0);
+        assert_eq!(summary.median, 2.5);
+        assert_eq!(summary.mean, 2.5);
+        assert!((summary.stddev - (5.0f64 / 3.0).sqrt()).abs() < 1e-12);
+        assert_eq!(Summary::new(&[7.0]), Summary { min: 7.0, median: 7.0, mean: 7.0, stddev: 0.0 });
+    }
+
+    #[test]
+    fn measure_test() {
+        let mut runs = Vec::new();
+        let options = BenchOptions { repetitions: 3, warmup: 2 };
+        measure(options, 2, |query| runs.push(query));
+        assert_eq!(runs, vec![0, 1, 0, 1, 0, 1, 0, 1]);
+        assert_eq!(measure(options, 0, |_| unreachable!()), Summary::default());
+        let total = count(3, |query| SearchStats { scanned_nodes: 1, scanned_arcs: 2, relaxations: query, ..SearchStats::default() });
+        assert_eq!(total, SearchStats { scanned_nodes: 3, scanned_arcs: 6, relaxations: 3, ..SearchStats::default() });
+
+        let measurement = Measurement {
+            algorithm: "dial".to_string(),
+            graph: display_name("C:\\Users\\x\\TEST\\Random4-n.10.0.gr"),
+            challenge: display_name("/data/Random4-n.10.0.ss"),
+            node_quantity: 1024,
+            arc_quantity: 4096,
+            queries: 2,
+            repetitions: 3,
+            summary: Summary::new(&[0.5, 1.5]),
+            scanned_nodes: Some(1024.0),
+            relaxations: None,
+        };
+        let mut out: Vec<u8> = Vec::new();
+        measurement.write_csv(&mut out).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "dial;Random4-n.10.0.gr;Random4-n.10.0.ss;1024;4096;2;3;0.500000000;1.000000000;1.000000000;0.707106781;1024.0;\n"
+        );
+    }
+}
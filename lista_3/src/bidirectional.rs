@@ -0,0 +1,146 @@
+use std::collections::BinaryHeap;
+
+use crate::csr::Csr;
+use crate::stats::{self, Event, NoStats, Stats};
+use crate::{Graph, SearchNode};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Meeting {
+    pub distance: usize,
+    //node on a shortest path where the two searches met
+    pub node: usize,
+}
+
+//one direction of the search, adj is either the forward or the reverse adjacency
+struct Side<'a> {
+    adj: &'a Csr,
+    distances: Vec<Option<usize>>,
+    settled: Vec<bool>,
+    queue: BinaryHeap<SearchNode>,
+}
+
+impl<'a> Side<'a> {
+    fn new(adj: &'a Csr, source: usize) -> Self {
+        let mut distances = vec![None; adj.node_quantity()];
+        distances[source] = Some(0);
+        let mut queue = BinaryHeap::new();
+        queue.push(SearchNode { id: source, distance: 0 });
+        Side { adj, distances, settled: vec![false; adj.node_quantity()], queue }
+    }
+
+    //smallest key still in the queue, stale entries only make it smaller so the bound stays valid
+    fn top(&self) -> Option<usize> {
+        self.queue.peek().map(|node| node.distance)
+    }
+
+    //settles the next node and relaxes its arcs, best is updated for every arc reaching a node the other side has labelled
+    fn scan<S: Stats>(&mut self, other: &[Option<usize>], best: &mut Option<Meeting>, stats: &mut S) {
+        let Some(SearchNode { id: node, distance }) = self.queue.pop() else {
+            return;
+        };
+        stats.record(Event::Pop);
+        if self.settled[node] {
+            return;
+        }
+        self.settled[node] = true;
+        stats.record(Event::ScannedNode);
+        for (to, cost) in self.adj.arcs(node) {
+            stats.record(Event::ScannedArc);
+            let new_distance = distance + cost;
+            if self.distances[to].is_none_or(|old| new_distance < old) {
+                self.distances[to] = Some(new_distance);
+                self.queue.push(SearchNode { id: to, distance: new_distance });
+                stats.record(Event::Relaxation);
+                stats.record(Event::Push);
+            }
+            if let Some(rest) = other[to] {
+                if best.is_none_or(|meeting| new_distance + rest < meeting.distance) {
+                    *best = Some(Meeting { distance: new_distance + rest, node: to + 1 });
+                }
+            }
+        }
+    }
+}
+
+impl Graph {
+    //Dijkstra from start over adj and from goal over radj, always advancing the side with the smaller key.
+    //Stops once the two keys add up to at least the best meeting found, no shorter path can be left then
+    pub fn bidirectional_djikstra_p2p(&self, start: usize, goal: usize) -> Option<Meeting> {
+        self.bidirectional_djikstra_with(start, goal, &mut NoStats)
+    }
+
+    pub fn bidirectional_djikstra_p2p_stats<S: Stats>(&self, start: usize, goal: usize) -> (Option<Meeting>, S) {
+        stats::collect(|stats| self.bidirectional_djikstra_with(start, goal, stats))
+    }
+
+    fn bidirectional_djikstra_with<S: Stats>(&self, start: usize, goal: usize, stats: &mut S) -> Option<Meeting> {
+        if start == goal {
+            return Some(Meeting { distance: 0, node: start });
+        }
+        let mut forward = Side::new(&self.adj, start - 1);
+        let mut backward = Side::new(self.radj(), goal - 1);
+        stats.record_many(Event::Push, 2);
+        let mut best: Option<Meeting> = None;
+        while let (Some(top_forward), Some(top_backward)) = (forward.top(), backward.top()) {
+            if best.is_some_and(|meeting| top_forward + top_backward >= meeting.distance) {
+                break;
+            }
+            if top_forward <= top_backward {
+                forward.scan(&backward.distances, &mut best, stats);
+            } else {
+                backward.scan(&forward.distances, &mut best, stats);
+            }
+        }
+        best
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bidirectional::*;
+    use crate::random_graph;
+    use crate::stats::SearchStats;
+
+    #[test]
+    fn bidirectional_test() {
+        let mut graph = Graph::new(6);
+        graph.add_edge(1, 2, 13);
+        graph.add_edge(1, 3, 0);
+        graph.add_edge(1, 4, 15);
+        graph.add_edge(1, 5, 20);
+        graph.add_edge(2, 4, 5);
+        graph.add_edge(3, 5, 9);
+        graph.add_edge(4, 6, 2);
+        graph.add_edge(5, 6, 4);
+        let meeting = graph.bidirectional_djikstra_p2p(1, 6).unwrap();
+        assert_eq!(meeting.distance, 13);
+        assert!([3, 5, 6].contains(&meeting.node));
+        assert_eq!(graph.bidirectional_djikstra_p2p(6, 1), None);
+        assert_eq!(graph.bidirectional_djikstra_p2p(4, 4), Some(Meeting { distance: 0, node: 4 }));
+    }
+
+    #[test]
+    fn bidirectional_random_test() {
+        for seed in 1..40 {
+            let graph = random_graph(30, 90, 12, seed);
+            for start in [1, 7, 30] {
+                let tree = graph.djikstra_classic_tree(start);
+                for goal in 1..=30 {
+                    let expected = tree.distance(goal);
+                    let meeting = graph.bidirectional_djikstra_p2p(start, goal);
+                    assert_eq!(meeting.map(|meeting| meeting.distance), expected);
+                    let (counted, stats) = graph.bidirectional_djikstra_p2p_stats::<SearchStats>(start, goal);
+                    assert_eq!(counted, meeting);
+                    assert!(stats.scanned_nodes <= stats.pops && stats.pops <= stats.pushes);
+                    if let Some(meeting) = meeting {
+                        //the meeting node splits a shortest path into two shortest paths
+                        let to_goal = graph.djikstra_classic_p2p(meeting.node, goal).unwrap();
+                        assert_eq!(tree.distance(meeting.node).unwrap() + to_goal, meeting.distance);
+                    }
+                    assert_eq!(graph.dial_p2p(start, goal), expected);
+                    assert_eq!(graph.radix_p2p(start, goal), expected);
+                }
+            }
+        }
+    }
+}
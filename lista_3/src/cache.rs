@@ -0,0 +1,238 @@
+use std::fmt;
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+use std::sync::{Arc, OnceLock};
+
+use memmap2::Mmap;
+
+use crate::csr::{Csr, Words};
+use crate::Graph;
+
+const MAGIC: &[u8; 8] = b"AODGRAPH";
+//bumped on every change of the layout, older files are rebuilt
+pub const CACHE_VERSION: u32 = 3;
+const HEADER_SIZE: usize = 40;
+
+#[derive(Debug)]
+pub enum CacheError {
+    Io(io::Error),
+    NotACache,
+    Version { found: u32 },
+    //file shorter or longer than its header says
+    Size { expected: u64, found: u64 },
+    Checksum { expected: u32, found: u32 },
+    //checksum matched but the arrays do not form a graph
+    Corrupt(&'static str),
+}
+
+impl fmt::Display for CacheError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CacheError::Io(source) => write!(f, "{source}"),
+            CacheError::NotACache => write!(f, "not a graph cache"),
+            CacheError::Version { found } => write!(f, "cache version {found}, expected {CACHE_VERSION}"),
+            CacheError::Size { expected, found } => write!(f, "cache should have {expected} bytes, has {found}"),
+            CacheError::Checksum { expected, found } => write!(f, "checksum {found:08x}, expected {expected:08x}"),
+            CacheError::Corrupt(what) => write!(f, "corrupt cache: {what}"),
+        }
+    }
+}
+
+impl std::error::Error for CacheError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            CacheError::Io(source) => Some(source),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for CacheError {
+    fn from(error: io::Error) -> Self {
+        CacheError::Io(error)
+    }
+}
+
+//Binary graph file, little endian. Header: magic, version u32, crc32 of everything after it u32, node count u64,
+//arc count u64, max weight u64. Then first_out, heads and weights of the forward CSR as u32 arrays, the
+//reverse one is rebuilt when needed. Every array starts at a multiple of 4, so a mapped file is used in place
+impl Graph {
+    pub fn write_cache<W: Write>(&self, out: &mut W) -> io::Result<()> {
+        let arrays = arrays(self);
+        let counts = [self.node_quantity, self.arc_quantity(), self.max_weight].map(|value| (value as u64).to_le_bytes());
+        let mut hasher = crc32fast::Hasher::new();
+        for count in &counts {
+            hasher.update(count);
+        }
+        for array in arrays {
+            for word in array.iter() {
+                hasher.update(&word.to_le_bytes());
+            }
+        }
+        out.write_all(MAGIC)?;
+        out.write_all(&CACHE_VERSION.to_le_bytes())?;
+        out.write_all(&hasher.finalize().to_le_bytes())?;
+        for count in &counts {
+            out.write_all(count)?;
+        }
+        for array in arrays {
+            for word in array.iter() {
+                out.write_all(&word.to_le_bytes())?;
+            }
+        }
+        Ok(())
+    }
+
+    pub fn save_cache<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let mut out = BufWriter::new(File::create(path)?);
+        self.write_cache(&mut out)?;
+        out.flush()
+    }
+
+    //Maps the file and checks it, the arrays are then read straight from the mapping. On big endian
+    //machines they are decoded into memory instead
+    pub fn open_cache<P: AsRef<Path>>(path: P) -> Result<Graph, CacheError> {
+        let file = File::open(path)?;
+        //SAFETY: the cache is only written whole by save_cache, a file changed under the mapping is outside the contract
+        let map = Arc::new(unsafe { Mmap::map(&file)? });
+        if map.len() < HEADER_SIZE || &map[..8] != MAGIC {
+            return Err(CacheError::NotACache);
+        }
+        let version = u32::from_le_bytes(map[8..12].try_into().unwrap());
+        if version != CACHE_VERSION {
+            return Err(CacheError::Version { found: version });
+        }
+        let checksum = u32::from_le_bytes(map[12..16].try_into().unwrap());
+        let header = |at: usize| u64::from_le_bytes(map[at..at + 8].try_into().unwrap());
+        let (node_quantity, arc_quantity, max_weight) = (header(16), header(24), header(32));
+        //the counts are checksummed too, but the size they give is checked before hashing
+        let words = arc_quantity.checked_mul(2).and_then(|arcs| arcs.checked_add(node_quantity)).and_then(|words| words.checked_add(1));
+        let expected = words
+            .and_then(|words| words.checked_mul(4))
+            .and_then(|bytes| bytes.checked_add(HEADER_SIZE as u64))
+            .ok_or(CacheError::Corrupt("header counts overflow"))?;
+        if map.len() as u64 != expected {
+            return Err(CacheError::Size { expected, found: map.len() as u64 });
+        }
+        let found = crc32fast::hash(&map[16..]);
+        if found != checksum {
+            return Err(CacheError::Checksum { expected: checksum, found });
+        }
+        let (node_quantity, arc_quantity) = (node_quantity as usize, arc_quantity as usize);
+        let mut start = HEADER_SIZE;
+        let mut next = |len: usize| {
+            let words = if cfg!(target_endian = "little") {
+                Words::Mapped { map: map.clone(), start, len }
+            } else {
+                Words::Owned(map[start..start + 4 * len].chunks_exact(4).map(|word| u32::from_le_bytes(word.try_into().unwrap())).collect())
+            };
+            start += 4 * len;
+            words
+        };
+        let adj = Csr { first_out: next(node_quantity + 1), heads: next(arc_quantity), weights: next(arc_quantity) };
+        check(&adj, node_quantity, arc_quantity)?;
+        //the buckets of Dial and delta-stepping are sized from it
+        if adj.weights.iter().max().map_or(0, |cost| *cost as u64) != max_weight {
+            return Err(CacheError::Corrupt("max weight differs from the arcs"));
+        }
+        Ok(Graph { node_quantity, max_weight: max_weight as usize, adj, radj: OnceLock::new() })
+    }
+}
+
+fn arrays(graph: &Graph) -> [&Words; 3] {
+    [&graph.adj.first_out, &graph.adj.heads, &graph.adj.weights]
+}
+
+//offsets from 0 to the arc count without going down, heads inside the graph
+fn check(csr: &Csr, node_quantity: usize, arc_quantity: usize) -> Result<(), CacheError> {
+    if csr.first_out[0] != 0 || csr.first_out[node_quantity] as usize != arc_quantity {
+        return Err(CacheError::Corrupt("offsets do not span the arcs"));
+    }
+    if csr.first_out.windows(2).any(|pair| pair[0] > pair[1]) {
+        return Err(CacheError::Corrupt("offsets go down"));
+    }
+    if csr.heads.iter().any(|head| *head as usize >= node_quantity) {
+        return Err(CacheError::Corrupt("arc head out of range"));
+    }
+    Ok(())
+}
+
+//<graph>.bin next to the .gr file
+pub fn cache_path(graph_file: &str) -> String {
+    format!("{graph_file}.bin")
+}
+
+//the cache is used only when it was written after the graph file last changed
+pub fn cache_is_fresh<P: AsRef<Path>, Q: AsRef<Path>>(graph_file: P, cache_file: Q) -> bool {
+    let modified = |path: &Path| fs::metadata(path).and_then(|metadata| metadata.modified()).ok();
+    match (modified(graph_file.as_ref()), modified(cache_file.as_ref())) {
+        (Some(graph), Some(cache)) => cache >= graph,
+        //no graph file left, the cache is all there is
+        (None, Some(_)) => true,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, SystemTime};
+
+    use crate::cache::*;
+    use crate::random_graph;
+
+    #[test]
+    fn cache_test() {
+        let directory = std::env::temp_dir().join(format!("lista_3_cache_test_{}", std::process::id()));
+        fs::create_dir_all(&directory).unwrap();
+        let path = directory.join("random.gr.bin");
+        let graph = random_graph(300, 1500, 1000, 9);
+        graph.save_cache(&path).unwrap();
+        let mut loaded = Graph::open_cache(&path).unwrap();
+        assert_eq!(loaded.node_quantity(), 300);
+        assert_eq!(loaded.arc_quantity(), graph.arc_quantity());
+        assert_eq!(loaded.max_weight(), graph.max_weight());
+        for src in [1, 150, 300] {
+            assert_eq!(loaded.djikstra_classic_ss(src), graph.djikstra_classic_ss(src));
+            assert_eq!(loaded.reversed().dial_ss(src), graph.reversed().dial_ss(src));
+        }
+        //a mapped graph still takes new arcs
+        loaded.add_edge(1, 300, 0);
+        assert_eq!(loaded.djikstra_classic_p2p(1, 300), Some(0));
+
+        let mut bytes = fs::read(&path).unwrap();
+        bytes[HEADER_SIZE + 10] ^= 1;
+        fs::write(&path, &bytes).unwrap();
+        assert!(matches!(Graph::open_cache(&path), Err(CacheError::Checksum { .. })));
+        bytes[HEADER_SIZE + 10] ^= 1;
+        //the counts are checksummed too, a forged max weight with a matching checksum is still refused
+        bytes[32] ^= 1;
+        fs::write(&path, &bytes).unwrap();
+        assert!(matches!(Graph::open_cache(&path), Err(CacheError::Checksum { .. })));
+        let forged = crc32fast::hash(&bytes[16..]).to_le_bytes();
+        bytes[12..16].copy_from_slice(&forged);
+        fs::write(&path, &bytes).unwrap();
+        assert!(matches!(Graph::open_cache(&path), Err(CacheError::Corrupt(_))));
+        let mut overflowing = bytes.clone();
+        overflowing[16..24].copy_from_slice(&u64::MAX.to_le_bytes());
+        fs::write(&path, &overflowing).unwrap();
+        assert!(matches!(Graph::open_cache(&path), Err(CacheError::Corrupt("header counts overflow"))));
+        bytes.truncate(bytes.len() - 4);
+        fs::write(&path, &bytes).unwrap();
+        assert!(matches!(Graph::open_cache(&path), Err(CacheError::Size { .. })));
+        bytes[8] = 0;
+        fs::write(&path, &bytes).unwrap();
+        assert!(matches!(Graph::open_cache(&path), Err(CacheError::Version { found: 0 })));
+        fs::write(&path, b"p sp 1 0\n").unwrap();
+        assert!(matches!(Graph::open_cache(&path), Err(CacheError::NotACache)));
+
+        let graph_file = directory.join("random.gr");
+        fs::write(&graph_file, "p sp 1 0\n").unwrap();
+        let cache = File::options().write(true).open(&path).unwrap();
+        cache.set_modified(SystemTime::now() - Duration::from_secs(60)).unwrap();
+        assert!(!cache_is_fresh(&graph_file, &path));
+        cache.set_modified(SystemTime::now() + Duration::from_secs(60)).unwrap();
+        assert!(cache_is_fresh(&graph_file, &path));
+        fs::remove_dir_all(&directory).unwrap();
+    }
+}
@@ -0,0 +1,513 @@
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, BinaryHeap, HashMap};
+use std::fs::File;
+use std::io::{self, BufRead, BufWriter, Write};
+use std::path::Path;
+
+use crate::dimacs::{self, DimacsError};
+use crate::stats::{self, Event, NoStats, Stats};
+use crate::{Graph, SearchNode};
+
+//nodes a witness search may settle before giving up and keeping the shortcut
+const WITNESS_SETTLED_LIMIT: usize = 500;
+
+//arc of the hierarchy, middle is the node a shortcut bypasses (0-based like node)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct HierarchyArc {
+    node: usize,
+    cost: usize,
+    middle: Option<usize>,
+}
+
+//nodes are contracted one by one from the least important, every contraction adds shortcuts between
+//the remaining neighbours so distances among them stay the same. Queries only go upwards in the order
+#[derive(Clone, Debug, PartialEq)]
+pub struct ContractionHierarchy {
+    //position in the contraction order, indexed by node - 1
+    rank: Vec<usize>,
+    //arcs u -> v with rank[v] > rank[u], stored at u
+    up: Vec<Vec<HierarchyArc>>,
+    //arcs v -> u with rank[v] > rank[u], stored at u with node = v
+    down: Vec<Vec<HierarchyArc>>,
+}
+
+//ordered so that two contractions of the same graph pick the same shortcuts and write the same file
+type Arcs = BTreeMap<usize, (usize, Option<usize>)>;
+
+//remaining graph during the preprocessing, at most one (cheapest) arc per pair of nodes
+struct Contraction {
+    outgoing: Vec<Arcs>,
+    incoming: Vec<Arcs>,
+    contracted: Vec<bool>,
+    contracted_neighbours: Vec<usize>,
+    witness: Witness,
+}
+
+//Dijkstra from a neighbour of the node being contracted that avoids it. The buffers are reused
+//between searches and only the touched entries are reset
+struct Witness {
+    distances: Vec<usize>,
+    touched: Vec<usize>,
+    queue: BinaryHeap<SearchNode>,
+}
+
+impl Witness {
+    //stops at limit, after WITNESS_SETTLED_LIMIT nodes or once every head of an arc leaving skipped is settled
+    fn run(&mut self, outgoing: &[Arcs], source: usize, skipped: usize, limit: usize) {
+        for node in self.touched.drain(..) {
+            self.distances[node] = usize::MAX;
+        }
+        self.queue.clear();
+        self.distances[source] = 0;
+        self.touched.push(source);
+        self.queue.push(SearchNode { id: source, distance: 0 });
+        let mut targets = outgoing[skipped].len() - usize::from(outgoing[skipped].contains_key(&source));
+        let mut settled = 0;
+        while let Some(SearchNode { id: node, distance }) = self.queue.pop() {
+            if distance > self.distances[node] {
+                continue;
+            }
+            if node != source && outgoing[skipped].contains_key(&node) {
+                targets -= 1;
+            }
+            settled += 1;
+            if targets == 0 || settled > WITNESS_SETTLED_LIMIT {
+                break;
+            }
+            for (to, (cost, _)) in &outgoing[node] {
+                let new_distance = distance + cost;
+                if *to != skipped && new_distance <= limit && new_distance < self.distances[*to] {
+                    if self.distances[*to] == usize::MAX {
+                        self.touched.push(*to);
+                    }
+                    self.distances[*to] = new_distance;
+                    self.queue.push(SearchNode { id: *to, distance: new_distance });
+                }
+            }
+        }
+    }
+}
+
+impl Contraction {
+    fn new(graph: &Graph) -> Self {
+        let mut contraction = Contraction {
+            outgoing: vec![BTreeMap::new(); graph.node_quantity],
+            incoming: vec![BTreeMap::new(); graph.node_quantity],
+            contracted: vec![false; graph.node_quantity],
+            contracted_neighbours: vec![0; graph.node_quantity],
+            witness: Witness {
+                distances: vec![usize::MAX; graph.node_quantity],
+                touched: Vec::new(),
+                queue: BinaryHeap::new(),
+            },
+        };
+        for from in 0..graph.node_quantity {
+            for (to, cost) in graph.arcs(from) {
+                contraction.insert(from, to, cost, None);
+            }
+        }
+        contraction
+    }
+
+    //loops never lie on a shortest path and are dropped
+    fn insert(&mut self, from: usize, to: usize, cost: usize, middle: Option<usize>) {
+        if from == to || self.outgoing[from].get(&to).is_some_and(|(old, _)| *old <= cost) {
+            return;
+        }
+        self.outgoing[from].insert(to, (cost, middle));
+        self.incoming[to].insert(from, (cost, middle));
+    }
+
+    //(from, to, cost) for every path through node without a witness of at most the same cost
+    fn shortcuts(&mut self, node: usize) -> Vec<(usize, usize, usize)> {
+        let Contraction { outgoing, incoming, witness, .. } = self;
+        let mut shortcuts = Vec::new();
+        let Some(longest) = outgoing[node].values().map(|(cost, _)| *cost).max() else {
+            return shortcuts;
+        };
+        for (from, (cost_in, _)) in &incoming[node] {
+            witness.run(outgoing, *from, node, cost_in + longest);
+            for (to, (cost_out, _)) in &outgoing[node] {
+                let cost = cost_in + cost_out;
+                if to != from && witness.distances[*to] > cost {
+                    shortcuts.push((*from, *to, cost));
+                }
+            }
+        }
+        shortcuts
+    }
+
+    //edge difference, shortcuts added minus arcs removed, plus contracted neighbours to spread the contractions evenly
+    fn priority(&self, node: usize, shortcut_quantity: usize) -> i64 {
+        let removed = self.outgoing[node].len() + self.incoming[node].len();
+        shortcut_quantity as i64 - removed as i64 + self.contracted_neighbours[node] as i64
+    }
+
+    //shortcuts are the ones the priority of node was just computed from
+    fn contract(&mut self, node: usize, shortcuts: Vec<(usize, usize, usize)>, hierarchy: &mut ContractionHierarchy) {
+        for (to, (cost, middle)) in std::mem::take(&mut self.outgoing[node]) {
+            hierarchy.up[node].push(HierarchyArc { node: to, cost, middle });
+            self.incoming[to].remove(&node);
+            self.contracted_neighbours[to] += 1;
+        }
+        for (from, (cost, middle)) in std::mem::take(&mut self.incoming[node]) {
+            hierarchy.down[node].push(HierarchyArc { node: from, cost, middle });
+            self.outgoing[from].remove(&node);
+            self.contracted_neighbours[from] += 1;
+        }
+        for (from, to, cost) in shortcuts {
+            self.insert(from, to, cost, Some(node));
+        }
+        self.contracted[node] = true;
+    }
+}
+
+//labels of one side of the query, (distance, previous node on the upward path)
+type Labels = HashMap<usize, (usize, Option<usize>)>;
+
+impl ContractionHierarchy {
+    //node ordering by lazily updated priorities, a popped node is contracted only if its
+    //recomputed priority is still the smallest
+    pub fn new(graph: &Graph) -> Self {
+        let node_quantity = graph.node_quantity;
+        let mut hierarchy = ContractionHierarchy {
+            rank: vec![0; node_quantity],
+            up: vec![Vec::new(); node_quantity],
+            down: vec![Vec::new(); node_quantity],
+        };
+        let mut contraction = Contraction::new(graph);
+        let mut queue: BinaryHeap<Reverse<(i64, usize)>> = (0..node_quantity)
+            .map(|node| {
+                let shortcut_quantity = contraction.shortcuts(node).len();
+                Reverse((contraction.priority(node, shortcut_quantity), node))
+            })
+            .collect();
+        let mut next_rank = 0;
+        while let Some(Reverse((_, node))) = queue.pop() {
+            if contraction.contracted[node] {
+                continue;
+            }
+            let shortcuts = contraction.shortcuts(node);
+            let priority = contraction.priority(node, shortcuts.len());
+            if queue.peek().is_some_and(|Reverse((next, _))| priority > *next) {
+                queue.push(Reverse((priority, node)));
+                continue;
+            }
+            contraction.contract(node, shortcuts, &mut hierarchy);
+            hierarchy.rank[node] = next_rank;
+            next_rank += 1;
+        }
+        hierarchy
+    }
+
+    pub fn node_quantity(&self) -> usize {
+        self.rank.len()
+    }
+
+    //arcs of the hierarchy, original arcs included
+    pub fn arc_quantity(&self) -> usize {
+        self.up.iter().chain(&self.down).map(|arcs| arcs.len()).sum()
+    }
+
+    pub fn shortcut_quantity(&self) -> usize {
+        self.up.iter().chain(&self.down).flatten().filter(|arc| arc.middle.is_some()).count()
+    }
+
+    pub fn distance(&self, src: usize, dst: usize) -> Option<usize> {
+        self.search(src - 1, dst - 1, &mut NoStats).map(|(distance, _, _, _)| distance)
+    }
+
+    pub fn distance_stats<S: Stats>(&self, src: usize, dst: usize) -> (Option<usize>, S) {
+        stats::collect(|stats| self.search(src - 1, dst - 1, stats).map(|(distance, _, _, _)| distance))
+    }
+
+    //(distance, nodes from src to dst) with every shortcut unpacked into the arcs of the graph
+    pub fn path(&self, src: usize, dst: usize) -> Option<(usize, Vec<usize>)> {
+        let (distance, meeting, forward, backward) = self.search(src - 1, dst - 1, &mut NoStats)?;
+        let mut upward = vec![meeting];
+        while let Some((_, Some(previous))) = forward.get(upward.last().unwrap()) {
+            upward.push(*previous);
+        }
+        upward.reverse();
+        while let Some((_, Some(next))) = backward.get(upward.last().unwrap()) {
+            upward.push(*next);
+        }
+        let mut path = vec![src];
+        for pair in upward.windows(2) {
+            self.unpack(pair[0], pair[1], &mut path);
+        }
+        Some((distance, path))
+    }
+
+    //Dijkstra upwards from both ends, a side stops once its smallest key cannot improve the best meeting.
+    //The highest node of a shortest path is settled by both sides, so checking settled nodes is enough
+    fn search<S: Stats>(&self, src: usize, dst: usize, stats: &mut S) -> Option<(usize, usize, Labels, Labels)> {
+        let mut forward: Labels = HashMap::from([(src, (0, None))]);
+        let mut backward: Labels = HashMap::from([(dst, (0, None))]);
+        let mut forward_queue = BinaryHeap::from([SearchNode { id: src, distance: 0 }]);
+        let mut backward_queue = BinaryHeap::from([SearchNode { id: dst, distance: 0 }]);
+        stats.record_many(Event::Push, 2);
+        let mut best: Option<(usize, usize)> = None;
+        loop {
+            let bound = best.map_or(usize::MAX, |(distance, _)| distance);
+            let forward_top = forward_queue.peek().map(|node| node.distance).filter(|top| *top < bound);
+            let backward_top = backward_queue.peek().map(|node| node.distance).filter(|top| *top < bound);
+            let (queue, labels, other, arcs, stall_arcs) = match (forward_top, backward_top) {
+                (None, None) => break,
+                (Some(f), Some(b)) if b < f => (&mut backward_queue, &mut backward, &forward, &self.down, &self.up),
+                (Some(_), _) => (&mut forward_queue, &mut forward, &backward, &self.up, &self.down),
+                (None, Some(_)) => (&mut backward_queue, &mut backward, &forward, &self.down, &self.up),
+            };
+            let SearchNode { id: node, distance } = queue.pop().unwrap();
+            stats.record(Event::Pop);
+            if distance > labels[&node].0 {
+                continue;
+            }
+            if let Some((rest, _)) = other.get(&node) {
+                if best.is_none_or(|(old, _)| distance + rest < old) {
+                    best = Some((distance + rest, node));
+                }
+            }
+            //stall on demand, a higher node reaching this one more cheaply means the label is not final
+            //and nothing found through it can be shortest
+            if stall_arcs[node].iter().any(|arc| labels.get(&arc.node).is_some_and(|(higher, _)| higher + arc.cost < distance)) {
+                continue;
+            }
+            stats.record(Event::ScannedNode);
+            for arc in &arcs[node] {
+                stats.record(Event::ScannedArc);
+                let new_distance = distance + arc.cost;
+                if labels.get(&arc.node).is_none_or(|(old, _)| new_distance < *old) {
+                    labels.insert(arc.node, (new_distance, Some(node)));
+                    queue.push(SearchNode { id: arc.node, distance: new_distance });
+                    stats.record(Event::Relaxation);
+                    stats.record(Event::Push);
+                }
+            }
+        }
+        let (distance, meeting) = best?;
+        Some((distance, meeting, forward, backward))
+    }
+
+    //the arc from -> to of the hierarchy, it is stored at whichever end has the lower rank
+    fn find_arc(&self, from: usize, to: usize) -> Option<HierarchyArc> {
+        let (arcs, other) = if self.rank[from] < self.rank[to] { (&self.up[from], to) } else { (&self.down[to], from) };
+        arcs.iter().find(|arc| arc.node == other).copied()
+    }
+
+    //parse checks every shortcut, so the halves of an arc of a loaded hierarchy are always there
+    fn arc(&self, from: usize, to: usize) -> HierarchyArc {
+        self.find_arc(from, to).expect("arc of the hierarchy")
+    }
+
+    //appends the nodes after from on the original path of the arc from -> to, 1-based
+    fn unpack(&self, from: usize, to: usize, path: &mut Vec<usize>) {
+        let mut stack = vec![(from, to)];
+        while let Some((from, to)) = stack.pop() {
+            match self.arc(from, to).middle {
+                Some(middle) => {
+                    stack.push((middle, to));
+                    stack.push((from, middle));
+                },
+                None => path.push(to + 1),
+            }
+        }
+    }
+
+    //p ch <n> <m>, n lines o <node> in contraction order, m lines a <from> <to> <cost> <middle or 0>
+    pub fn write<W: Write>(&self, out: &mut W) -> io::Result<()> {
+        writeln!(out, "c contraction hierarchy")?;
+        writeln!(out, "p ch {} {}", self.node_quantity(), self.arc_quantity())?;
+        let mut order = vec![0; self.node_quantity()];
+        for (node, rank) in self.rank.iter().enumerate() {
+            order[*rank] = node;
+        }
+        for node in order {
+            writeln!(out, "o {}", node + 1)?;
+        }
+        for (node, arcs) in self.up.iter().enumerate() {
+            for arc in arcs {
+                writeln!(out, "a {} {} {} {}", node + 1, arc.node + 1, arc.cost, arc.middle.map_or(0, |middle| middle + 1))?;
+            }
+        }
+        for (node, arcs) in self.down.iter().enumerate() {
+            for arc in arcs {
+                writeln!(out, "a {} {} {} {}", arc.node + 1, node + 1, arc.cost, arc.middle.map_or(0, |middle| middle + 1))?;
+            }
+        }
+        Ok(())
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let mut out = BufWriter::new(File::create(path)?);
+        self.write(&mut out)?;
+        out.flush()
+    }
+
+    pub fn parse<R: BufRead>(reader: R) -> Result<Self, DimacsError> {
+        let mut header: Option<(usize, usize)> = None;
+        let mut order: Vec<usize> = Vec::new();
+        let mut arcs: Vec<(usize, usize, usize, usize, usize)> = Vec::new();
+        //grown up to the highest node ordered so far, not sized from the header
+        let mut rank: Vec<Option<usize>> = Vec::new();
+        dimacs::for_each_line(reader, |line, kind, fields| {
+            match (kind, header) {
+                ("p", None) => {
+                    dimacs::expect_problem(line, fields, "ch")?;
+                    let node_quantity = dimacs::count(line, fields, "node count", dimacs::NODE_LIMIT)?;
+                    let arc_quantity = dimacs::count(line, fields, "arc count", dimacs::COUNT_LIMIT)?;
+                    header = Some((node_quantity, arc_quantity));
+                },
+                ("p", Some(_)) => return Err(DimacsError::DuplicateProblemLine { line }),
+                ("o", Some((node_quantity, _))) => {
+                    let node = dimacs::node(line, fields, node_quantity)?;
+                    if rank.len() < node {
+                        rank.resize(node, None);
+                    }
+                    if rank[node - 1].replace(order.len()).is_some() {
+                        return Err(DimacsError::DuplicateNode { line, node });
+                    }
+                    order.push(node);
+                },
+                ("a", Some((node_quantity, _))) => {
+                    let from = dimacs::node(line, fields, node_quantity)?;
+                    let to = dimacs::node(line, fields, node_quantity)?;
+                    let cost = dimacs::field(line, fields, "arc cost")?;
+                    let middle: usize = dimacs::field(line, fields, "middle node")?;
+                    if middle > node_quantity || from == to {
+                        return Err(DimacsError::BadField { line, field: "arc", value: Some(format!("{from} {to} {cost} {middle}")) });
+                    }
+                    arcs.push((line, from - 1, to - 1, cost, middle));
+                },
+                ("o" | "a", None) => return Err(DimacsError::MissingProblemLine { line }),
+                _ => return Err(dimacs::unknown_line(line, kind, fields)),
+            }
+            Ok(())
+        })?;
+        let (node_quantity, arc_quantity) = header.ok_or(DimacsError::NoProblemLine)?;
+        dimacs::check_count("ordered nodes", node_quantity, order.len())?;
+        dimacs::check_count("arcs", arc_quantity, arcs.len())?;
+        let mut hierarchy = ContractionHierarchy {
+            rank: rank.into_iter().flatten().collect(),
+            up: vec![Vec::new(); node_quantity],
+            down: vec![Vec::new(); node_quantity],
+        };
+        for (_, from, to, cost, middle) in &arcs {
+            let arc = |node| HierarchyArc { node, cost: *cost, middle: middle.checked_sub(1) };
+            if hierarchy.rank[*from] < hierarchy.rank[*to] {
+                hierarchy.up[*from].push(arc(*to));
+            } else {
+                hierarchy.down[*to].push(arc(*from));
+            }
+        }
+        //a shortcut has to bypass a node contracted before both its ends through the two arcs it replaces,
+        //then unpacking always ends on original arcs
+        for (line, from, to, cost, middle) in arcs {
+            let Some(middle) = middle.checked_sub(1) else { continue };
+            let rank = &hierarchy.rank;
+            let halves = (rank[middle] < rank[from].min(rank[to]))
+                .then(|| hierarchy.find_arc(from, middle).zip(hierarchy.find_arc(middle, to)))
+                .flatten();
+            if halves.is_none_or(|(first, second)| first.cost.checked_add(second.cost) != Some(cost)) {
+                let value = format!("{} {} {cost} {}", from + 1, to + 1, middle + 1);
+                return Err(DimacsError::BadField { line, field: "shortcut", value: Some(value) });
+            }
+        }
+        Ok(hierarchy)
+    }
+
+    pub fn read<P: AsRef<Path>>(path: P) -> Result<Self, DimacsError> {
+        Self::parse(dimacs::open(path.as_ref())?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ch::*;
+    use crate::random_graph;
+    use crate::stats::SearchStats;
+
+    #[test]
+    fn contraction_hierarchy_test() {
+        let mut graph = Graph::new(6);
+        graph.add_edge(1, 2, 13);
+        graph.add_edge(1, 3, 0);
+        graph.add_edge(1, 4, 15);
+        graph.add_edge(1, 5, 20);
+        graph.add_edge(2, 4, 5);
+        graph.add_edge(3, 5, 9);
+        graph.add_edge(4, 6, 2);
+        graph.add_edge(5, 6, 4);
+        let hierarchy = ContractionHierarchy::new(&graph);
+        assert_eq!(hierarchy.path(1, 6), Some((13, vec![1, 3, 5, 6])));
+        assert_eq!(hierarchy.path(2, 6), Some((7, vec![2, 4, 6])));
+        assert_eq!(hierarchy.path(3, 3), Some((0, vec![3])));
+        assert_eq!(hierarchy.path(6, 1), None);
+    }
+
+    #[test]
+    fn contraction_hierarchy_random_test() {
+        for seed in 1..30 {
+            let graph = random_graph(40, 120, 15, seed);
+            let hierarchy = ContractionHierarchy::new(&graph);
+            for start in [1, 13, 40] {
+                let tree = graph.djikstra_classic_tree(start);
+                for goal in 1..=40 {
+                    let result = hierarchy.path(start, goal);
+                    assert_eq!(result.as_ref().map(|(distance, _)| *distance), tree.distance(goal));
+                    //both sides only climb, so the query scans at most every node once per side
+                    let (distance, stats) = hierarchy.distance_stats::<SearchStats>(start, goal);
+                    assert_eq!(distance, tree.distance(goal));
+                    assert!(stats.scanned_nodes <= 2 * 40 && stats.pushes == stats.relaxations + 2);
+                    if let Some((distance, path)) = result {
+                        assert_eq!((path[0], *path.last().unwrap()), (start, goal));
+                        assert_eq!(graph.path_length(&path), Some(distance));
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn contraction_hierarchy_file_test() {
+        let graph = random_graph(25, 80, 9, 7);
+        let hierarchy = ContractionHierarchy::new(&graph);
+        let mut file = Vec::new();
+        hierarchy.write(&mut file).unwrap();
+        let reloaded = ContractionHierarchy::parse(file.as_slice()).unwrap();
+        for start in 1..=25 {
+            for goal in 1..=25 {
+                assert_eq!(reloaded.path(start, goal), hierarchy.path(start, goal));
+            }
+        }
+        assert_eq!(reloaded.arc_quantity(), hierarchy.arc_quantity());
+        let mut again = Vec::new();
+        ContractionHierarchy::new(&graph).write(&mut again).unwrap();
+        assert_eq!(again, file);
+        assert!(matches!(
+            ContractionHierarchy::parse("p ch 2 0\no 1\no 1\n".as_bytes()),
+            Err(DimacsError::DuplicateNode { line: 3, node: 1 })
+        ));
+        assert!(matches!(
+            ContractionHierarchy::parse("p ch 18446744073709551615 0\no 1\n".as_bytes()),
+            Err(DimacsError::TooLarge { what: "node count", .. })
+        ));
+        assert!(matches!(
+            ContractionHierarchy::parse("p ch 4294967296 0\no 1\n".as_bytes()),
+            Err(DimacsError::CountMismatch { what: "ordered nodes", .. })
+        ));
+        assert!(matches!(
+            ContractionHierarchy::parse("p ch 2 1\no 1\no 2\na 1 2 5 3\n".as_bytes()),
+            Err(DimacsError::BadField { line: 4, .. })
+        ));
+        //1 -> 3 through 2 needs the arcs 1 -> 2 and 2 -> 3 of cost 5 together
+        let header = "p ch 3 3\no 2\no 1\no 3\na 1 2 2 0\n";
+        assert!(ContractionHierarchy::parse(format!("{header}a 2 3 3 0\na 1 3 5 2\n").as_bytes()).is_ok());
+        for arcs in ["a 2 1 3 0\na 1 3 5 2\n", "a 2 3 4 0\na 1 3 5 2\n", "a 2 3 3 0\na 2 3 5 1\n"] {
+            assert!(matches!(
+                ContractionHierarchy::parse(format!("{header}{arcs}").as_bytes()),
+                Err(DimacsError::BadField { line: 7, field: "shortcut", .. })
+            ));
+        }
+    }
+}
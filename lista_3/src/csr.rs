@@ -0,0 +1,159 @@
+use std::ops::Deref;
+use std::sync::Arc;
+
+use memmap2::Mmap;
+
+//u32 array held in memory or read in place from a memory mapped cache file
+#[derive(Clone, Debug)]
+pub(crate) enum Words {
+    Owned(Vec<u32>),
+    //start is a multiple of 4 and the file little endian, checked when the cache is opened
+    Mapped { map: Arc<Mmap>, start: usize, len: usize },
+}
+
+impl Words {
+    //a mapped array is copied out before its first change
+    fn to_mut(&mut self) -> &mut Vec<u32> {
+        if let Words::Mapped { .. } = self {
+            *self = Words::Owned(self.to_vec());
+        }
+        match self {
+            Words::Owned(words) => words,
+            Words::Mapped { .. } => unreachable!(),
+        }
+    }
+}
+
+impl Deref for Words {
+    type Target = [u32];
+
+    fn deref(&self) -> &[u32] {
+        match self {
+            Words::Owned(words) => words,
+            Words::Mapped { map, start, len } => {
+                let bytes = &map[*start..*start + *len * 4];
+                //SAFETY: the range is inside the map, page aligned plus a multiple of 4, and any bit pattern is a u32
+                unsafe { std::slice::from_raw_parts(bytes.as_ptr() as *const u32, *len) }
+            },
+        }
+    }
+}
+
+impl Default for Words {
+    fn default() -> Self {
+        Words::Owned(Vec::new())
+    }
+}
+
+impl PartialEq for Words {
+    fn eq(&self, other: &Self) -> bool {
+        **self == **other
+    }
+}
+
+impl Eq for Words {}
+
+//Frozen compressed sparse row adjacency: the arcs of node v are heads[first_out[v]..first_out[v + 1]]
+//with the same weights, in the order they were given. Node ids and costs are u32, 8 bytes per arc and
+//4 per node, parallel arcs are all kept
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Csr {
+    pub(crate) first_out: Words,
+    pub(crate) heads: Words,
+    pub(crate) weights: Words,
+}
+
+impl Csr {
+    pub fn new(node_quantity: usize) -> Self {
+        Csr { first_out: Words::Owned(vec![0; node_quantity + 1]), heads: Words::default(), weights: Words::default() }
+    }
+
+    //(from, to, cost) with 0-based nodes, counting sort by tail keeps the order of arcs leaving a node
+    pub fn from_arcs(node_quantity: usize, arcs: impl Iterator<Item = (usize, usize, usize)> + Clone) -> Self {
+        let mut first_out = vec![0u32; node_quantity + 1];
+        for (from, _, _) in arcs.clone() {
+            first_out[from + 1] += 1;
+        }
+        for node in 0..node_quantity {
+            first_out[node + 1] = first_out[node].checked_add(first_out[node + 1]).expect("more than u32::MAX arcs");
+        }
+        let arc_quantity = first_out[node_quantity] as usize;
+        let mut next = first_out.clone();
+        let (mut heads, mut weights) = (vec![0; arc_quantity], vec![0; arc_quantity]);
+        for (from, to, cost) in arcs {
+            let slot = next[from] as usize;
+            heads[slot] = id(to);
+            weights[slot] = weight(cost);
+            next[from] += 1;
+        }
+        Csr { first_out: Words::Owned(first_out), heads: Words::Owned(heads), weights: Words::Owned(weights) }
+    }
+
+    pub fn node_quantity(&self) -> usize {
+        self.first_out.len() - 1
+    }
+
+    pub fn arc_quantity(&self) -> usize {
+        self.heads.len()
+    }
+
+    //(to, cost) of the arcs leaving node, both 0-based
+    pub fn arcs(&self, node: usize) -> impl Iterator<Item = (usize, usize)> + Clone + '_ {
+        let range = self.first_out[node] as usize..self.first_out[node + 1] as usize;
+        self.heads[range.clone()].iter().zip(&self.weights[range]).map(|(to, cost)| (*to as usize, *cost as usize))
+    }
+
+    //the same arcs turned around, the ones entering a node ordered by tail
+    pub fn transpose(&self) -> Csr {
+        let arcs = (0..self.node_quantity()).flat_map(|from| self.arcs(from).map(move |(to, cost)| (to, from, cost)));
+        Csr::from_arcs(self.node_quantity(), arcs)
+    }
+
+    pub fn costs(&self) -> impl Iterator<Item = usize> + '_ {
+        self.weights.iter().map(|cost| *cost as usize)
+    }
+
+    //appends one arc to the arcs of from, O(n + m), meant for graphs built by hand. Files go through from_arcs
+    pub(crate) fn insert(&mut self, from: usize, to: usize, cost: usize) {
+        let slot = self.first_out[from + 1] as usize;
+        self.heads.to_mut().insert(slot, id(to));
+        self.weights.to_mut().insert(slot, weight(cost));
+        for offset in &mut self.first_out.to_mut()[from + 1..] {
+            *offset += 1;
+        }
+    }
+}
+
+//Graph::from_arcs checks the ranges before building, add_edge is for graphs built by hand
+fn id(node: usize) -> u32 {
+    u32::try_from(node).expect("node id does not fit in u32")
+}
+
+fn weight(cost: usize) -> u32 {
+    u32::try_from(cost).expect("arc cost does not fit in u32")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::csr::*;
+
+    #[test]
+    fn csr_test() {
+        let arcs = [(2, 0, 5), (0, 1, 3), (2, 1, 1), (0, 1, 3), (0, 2, 7)];
+        let csr = Csr::from_arcs(4, arcs.iter().copied());
+        assert_eq!(csr.node_quantity(), 4);
+        assert_eq!(csr.arc_quantity(), 5);
+        assert_eq!(csr.arcs(0).collect::<Vec<_>>(), vec![(1, 3), (1, 3), (2, 7)]);
+        assert_eq!(csr.arcs(1).count(), 0);
+        assert_eq!(csr.arcs(2).collect::<Vec<_>>(), vec![(0, 5), (1, 1)]);
+        assert_eq!(csr.arcs(3).count(), 0);
+        let mut inserted = Csr::new(4);
+        for (from, to, cost) in arcs {
+            inserted.insert(from, to, cost);
+        }
+        assert_eq!(inserted, csr);
+        let transposed = csr.transpose();
+        assert_eq!(transposed.arcs(1).collect::<Vec<_>>(), vec![(0, 3), (0, 3), (2, 1)]);
+        assert_eq!(transposed.transpose(), csr);
+    }
+}
@@ -0,0 +1,212 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use rayon::prelude::*;
+
+use crate::stats::{self, Event, NoStats, Stats};
+use crate::{Graph, ShortestPathTree};
+
+const UNREACHED: usize = usize::MAX;
+
+//Delta-stepping (Meyer, Sanders): bucket i holds nodes with tentative distance in [i * delta, (i + 1) * delta).
+//Arcs up to delta are light and may refill the bucket being emptied, so its nodes are relaxed phase by phase
+//until it stays empty; heavy arcs only reach later buckets and go once per bucket. Every phase relaxes its
+//nodes in parallel, tentative distances are atomics lowered with fetch_min. Delta 1 is Dial's algorithm,
+//delta above the largest cost is Bellman-Ford
+#[derive(Clone, Debug)]
+pub struct DeltaStepping {
+    delta: usize,
+    max_weight: usize,
+    //(to, cost) 0-based, cost <= delta
+    light: Vec<Vec<(usize, usize)>>,
+    heavy: Vec<Vec<(usize, usize)>>,
+}
+
+impl DeltaStepping {
+    pub fn new(graph: &Graph, delta: usize) -> Self {
+        assert!(delta > 0, "delta has to be positive");
+        let split = |node: usize, light: bool| graph.arcs(node).filter(|(_, cost)| (*cost <= delta) == light).collect();
+        DeltaStepping {
+            delta,
+            max_weight: graph.max_weight,
+            light: (0..graph.node_quantity).map(|node| split(node, true)).collect(),
+            heavy: (0..graph.node_quantity).map(|node| split(node, false)).collect(),
+        }
+    }
+
+    pub fn delta(&self) -> usize {
+        self.delta
+    }
+
+    pub fn ss(&self, src: usize) -> Vec<Option<usize>> {
+        self.ss_with(src, &mut NoStats)
+    }
+
+    //bucket entries count as pushes and pops, every node of a phase as scanned
+    pub fn ss_stats<S: Stats>(&self, src: usize) -> (Vec<Option<usize>>, S) {
+        stats::collect(|stats| self.ss_with(src, stats))
+    }
+
+    fn ss_with<S: Stats>(&self, src: usize, stats: &mut S) -> Vec<Option<usize>> {
+        let node_quantity = self.light.len();
+        let distances: Vec<AtomicUsize> = (0..node_quantity).map(|_| AtomicUsize::new(UNREACHED)).collect();
+        distances[src - 1].store(0, Ordering::Relaxed);
+        //pending distances never reach max_weight past the current bucket, so the buckets are reused cyclically.
+        //A node may sit in several buckets, entries whose node has moved on are skipped
+        let mut buckets: Vec<Vec<usize>> = vec![Vec::new(); self.max_weight / self.delta + 2];
+        buckets[0].push(src - 1);
+        stats.record(Event::Push);
+        let mut pending = 1;
+        let mut current = 0;
+        let mut in_bucket = vec![false; node_quantity];
+        while pending > 0 {
+            let slot = current % buckets.len();
+            stats.record(Event::BucketScan);
+            if buckets[slot].is_empty() {
+                current += 1;
+                continue;
+            }
+            let mut settled = Vec::new();
+            while !buckets[slot].is_empty() {
+                let entries = std::mem::take(&mut buckets[slot]);
+                pending -= entries.len();
+                stats.record_many(Event::Pop, entries.len());
+                let mut frontier = Vec::with_capacity(entries.len());
+                for node in entries {
+                    if !in_bucket[node] && distances[node].load(Ordering::Relaxed) / self.delta == current {
+                        in_bucket[node] = true;
+                        frontier.push(node);
+                    }
+                }
+                for node in &frontier {
+                    in_bucket[*node] = false;
+                }
+                stats.record_many(Event::ScannedNode, frontier.len());
+                let improved = self.relax(&distances, &frontier, &self.light, stats);
+                pending += self.insert(&mut buckets, improved, stats);
+                settled.extend(frontier);
+            }
+            settled.sort_unstable();
+            settled.dedup();
+            let improved = self.relax(&distances, &settled, &self.heavy, stats);
+            pending += self.insert(&mut buckets, improved, stats);
+            current += 1;
+        }
+        distances
+            .into_iter()
+            .map(|distance| Some(distance.into_inner()).filter(|distance| *distance != UNREACHED))
+            .collect()
+    }
+
+    //distances by delta-stepping, predecessors from a search over tight arcs afterwards. Picking any tight
+    //incoming arc could close a cycle of zero cost arcs
+    pub fn tree(&self, graph: &Graph, src: usize) -> ShortestPathTree {
+        let distances = self.ss(src);
+        let mut predecessors = vec![None; graph.node_quantity];
+        let mut reached = vec![false; graph.node_quantity];
+        reached[src - 1] = true;
+        let mut stack = vec![src - 1];
+        while let Some(from) = stack.pop() {
+            let distance = distances[from].unwrap();
+            for (to, cost) in graph.arcs(from) {
+                if !reached[to] && distances[to] == Some(distance + cost) {
+                    reached[to] = true;
+                    predecessors[to] = Some(from + 1);
+                    stack.push(to);
+                }
+            }
+        }
+        ShortestPathTree { source: src, distances, predecessors }
+    }
+
+    //(node, distance) pairs for every label lowered by the arcs out of nodes
+    fn relax<S: Stats>(&self, distances: &[AtomicUsize], nodes: &[usize], arcs: &[Vec<(usize, usize)>], stats: &mut S) -> Vec<(usize, usize)> {
+        stats.record_many(Event::ScannedArc, nodes.iter().map(|node| arcs[*node].len()).sum());
+        let improved: Vec<(usize, usize)> = nodes
+            .par_iter()
+            .flat_map_iter(|&from| {
+                let distance = distances[from].load(Ordering::Relaxed);
+                arcs[from].iter().filter_map(move |&(to, cost)| {
+                    let new_distance = distance + cost;
+                    (new_distance < distances[to].fetch_min(new_distance, Ordering::Relaxed)).then_some((to, new_distance))
+                })
+            })
+            .collect();
+        stats.record_many(Event::Relaxation, improved.len());
+        improved
+    }
+
+    fn insert<S: Stats>(&self, buckets: &mut [Vec<usize>], improved: Vec<(usize, usize)>, stats: &mut S) -> usize {
+        let inserted = improved.len();
+        stats.record_many(Event::Push, inserted);
+        for (node, distance) in improved {
+            let slot = distance / self.delta % buckets.len();
+            buckets[slot].push(node);
+        }
+        inserted
+    }
+}
+
+impl Graph {
+    //largest cost over the average out degree, a common starting point for tuning delta
+    pub fn default_delta(&self) -> usize {
+        let arc_quantity = self.arc_quantity().max(1);
+        (self.max_weight * self.node_quantity / arc_quantity).max(1)
+    }
+
+    pub fn delta_stepping_ss(&self, src: usize, delta: usize) -> Vec<Option<usize>> {
+        DeltaStepping::new(self, delta).ss(src)
+    }
+
+    pub fn delta_stepping_tree(&self, src: usize, delta: usize) -> ShortestPathTree {
+        DeltaStepping::new(self, delta).tree(self, src)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::delta_stepping::*;
+    use crate::random_graph;
+    use crate::stats::SearchStats;
+
+    #[test]
+    fn delta_stepping_test() {
+        let mut graph = Graph::new(5);
+        graph.add_edge(1, 2, 10);
+        graph.add_edge(1, 3, 3);
+        graph.add_edge(3, 2, 4);
+        graph.add_edge(2, 4, 2);
+        graph.add_edge(3, 4, 8);
+        graph.add_edge(4, 1, 1);
+        for delta in [1, 2, 3, 7, 100] {
+            assert_eq!(graph.delta_stepping_ss(1, delta), vec![Some(0), Some(7), Some(3), Some(9), None]);
+            let tree = graph.delta_stepping_tree(1, delta);
+            assert_eq!(tree.path(4), Some(vec![1, 3, 2, 4]));
+            assert_eq!(tree.path(5), None);
+            //every bucket entry is taken out again, every one but the source came from a relaxation
+            let (distances, stats) = DeltaStepping::new(&graph, delta).ss_stats::<SearchStats>(1);
+            assert_eq!(distances, graph.delta_stepping_ss(1, delta));
+            assert_eq!(stats.pops, stats.pushes);
+            assert_eq!(stats.relaxations + 1, stats.pushes);
+            assert!(stats.scanned_nodes >= 4 && stats.bucket_scans > 0);
+        }
+    }
+
+    #[test]
+    fn delta_stepping_random_test() {
+        for seed in 1..20 {
+            let graph = random_graph(200, 1000, 100, seed);
+            for delta in [1, 5, graph.default_delta(), 64, 1000] {
+                let stepping = DeltaStepping::new(&graph, delta);
+                for src in [1, 100, 200] {
+                    let tree = stepping.tree(&graph, src);
+                    assert_eq!(tree.distances, graph.djikstra_classic_ss(src));
+                    for node in 1..=200 {
+                        if let Some(path) = tree.path(node) {
+                            assert_eq!(graph.path_length(&path), tree.distance(node));
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
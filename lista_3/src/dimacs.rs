@@ -0,0 +1,483 @@
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::Path;
+use std::str::{FromStr, SplitWhitespace};
+
+use crate::signed::SignedGraph;
+use crate::{Algorithm, Graph};
+
+//node ids are stored as u32 counted from 0, arc and query counts as u32
+pub(crate) const NODE_LIMIT: usize = u32::MAX as usize + 1;
+pub(crate) const COUNT_LIMIT: usize = u32::MAX as usize;
+//header counts are not trusted with more than this much memory up front, the vectors grow past it
+const RESERVE_LIMIT: usize = 1 << 20;
+
+#[derive(Debug)]
+pub enum DimacsError {
+    Io { path: String, source: io::Error },
+    Read { line: usize, source: io::Error },
+    NoProblemLine,
+    //data line before the p line
+    MissingProblemLine { line: usize },
+    DuplicateProblemLine { line: usize },
+    //p line of another file type, e.g. a .p2p file given where a .ss is expected
+    WrongProblem { line: usize, expected: &'static str, found: String },
+    UnknownLine { line: usize, content: String },
+    BadField { line: usize, field: &'static str, value: Option<String> },
+    NodeOutOfRange { line: usize, node: usize, node_quantity: usize },
+    //node id of the arc-th arc handed to Graph::from_arcs, when there is no file line to point at
+    ArcNodeOutOfRange { arc: usize, node: usize, node_quantity: usize },
+    DuplicateNode { line: usize, node: usize },
+    //number of a/s/q/v lines differs from the p line
+    CountMismatch { what: &'static str, expected: usize, found: usize },
+    //node ids, costs and arc counts are stored as u32
+    TooLarge { what: &'static str, value: usize, limit: usize },
+}
+
+impl fmt::Display for DimacsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DimacsError::Io { path, source } => write!(f, "{path}: {source}"),
+            DimacsError::Read { line, source } => write!(f, "line {line}: {source}"),
+            DimacsError::NoProblemLine => write!(f, "no problem line"),
+            DimacsError::MissingProblemLine { line } => write!(f, "line {line}: data before the problem line"),
+            DimacsError::DuplicateProblemLine { line } => write!(f, "line {line}: second problem line"),
+            DimacsError::WrongProblem { line, expected, found } => {
+                write!(f, "line {line}: expected a '{expected}' problem, found '{found}'")
+            },
+            DimacsError::UnknownLine { line, content } => write!(f, "line {line}: unknown line '{content}'"),
+            DimacsError::BadField { line, field, value: Some(value) } => write!(f, "line {line}: invalid {field} '{value}'"),
+            DimacsError::BadField { line, field, value: None } => write!(f, "line {line}: missing {field}"),
+            DimacsError::NodeOutOfRange { line, node, node_quantity } => {
+                write!(f, "line {line}: node {node} out of range 1..={node_quantity}")
+            },
+            DimacsError::ArcNodeOutOfRange { arc, node, node_quantity } => {
+                write!(f, "arc {arc}: node {node} out of range 1..={node_quantity}")
+            },
+            DimacsError::DuplicateNode { line, node } => write!(f, "line {line}: node {node} listed twice"),
+            DimacsError::CountMismatch { what, expected, found } => {
+                write!(f, "problem line declares {expected} {what}, file has {found}")
+            },
+            DimacsError::TooLarge { what, value, limit } => write!(f, "{what} {value} above the limit of {limit}"),
+        }
+    }
+}
+
+impl std::error::Error for DimacsError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            DimacsError::Io { source, .. } | DimacsError::Read { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+//.gr file: p sp <n> <m> followed by m lines a <from> <to> <cost>, costs are i64 for graphs with negative arcs
+#[derive(Clone, Debug, PartialEq)]
+pub struct GraphFile<C = usize> {
+    pub node_quantity: usize,
+    pub arcs: Vec<(usize, usize, C)>,
+}
+
+impl GraphFile {
+    pub fn to_graph(&self) -> Result<Graph, DimacsError> {
+        Graph::from_arcs(self.node_quantity, &self.arcs)
+    }
+}
+
+impl<C: fmt::Display> GraphFile<C> {
+    pub fn write<W: Write>(&self, out: &mut W) -> io::Result<()> {
+        writeln!(out, "p sp {} {}", self.node_quantity, self.arcs.len())?;
+        for (from, to, cost) in &self.arcs {
+            writeln!(out, "a {from} {to} {cost}")?;
+        }
+        Ok(())
+    }
+}
+
+impl GraphFile<i64> {
+    pub fn to_signed_graph(&self) -> SignedGraph {
+        let mut graph = SignedGraph::new(self.node_quantity);
+        for &(from, to, cost) in &self.arcs {
+            graph.add_edge(from, to, cost);
+        }
+        graph
+    }
+}
+
+//.ss file (p aux sp ss <k>, s <node>) or .p2p file (p aux sp p2p <k>, q <from> <to>)
+#[derive(Clone, Debug, PartialEq)]
+pub enum Challenge {
+    Ss(Vec<usize>),
+    P2p(Vec<(usize, usize)>),
+}
+
+impl Challenge {
+    pub fn write<W: Write>(&self, out: &mut W) -> io::Result<()> {
+        match self {
+            Challenge::Ss(sources) => {
+                writeln!(out, "p aux sp ss {}", sources.len())?;
+                for src in sources {
+                    writeln!(out, "s {src}")?;
+                }
+            },
+            Challenge::P2p(queries) => {
+                writeln!(out, "p aux sp p2p {}", queries.len())?;
+                for (from, to) in queries {
+                    writeln!(out, "q {from} {to}")?;
+                }
+            },
+        }
+        Ok(())
+    }
+}
+
+//.co file: p aux sp co <n> followed by v <node> <x> <y>, indexed by node - 1
+#[derive(Clone, Debug, PartialEq)]
+pub struct Coordinates {
+    pub coordinates: Vec<(i64, i64)>,
+}
+
+pub fn parse_graph<R: BufRead>(reader: R) -> Result<GraphFile, DimacsError> {
+    parse_arcs(reader)
+}
+
+pub fn parse_signed_graph<R: BufRead>(reader: R) -> Result<GraphFile<i64>, DimacsError> {
+    parse_arcs(reader)
+}
+
+fn parse_arcs<R: BufRead, C: FromStr>(reader: R) -> Result<GraphFile<C>, DimacsError> {
+    let mut graph: Option<(GraphFile<C>, usize)> = None;
+    for_each_line(reader, |line, kind, fields| {
+        match (kind, &mut graph) {
+            ("p", None) => {
+                expect_problem(line, fields, "sp")?;
+                let node_quantity = count(line, fields, "node count", NODE_LIMIT)?;
+                let arc_quantity = count(line, fields, "arc count", COUNT_LIMIT)?;
+                graph = Some((GraphFile { node_quantity, arcs: Vec::with_capacity(arc_quantity.min(RESERVE_LIMIT)) }, arc_quantity));
+            },
+            ("p", Some(_)) => return Err(DimacsError::DuplicateProblemLine { line }),
+            ("a", Some((graph, _))) => {
+                let from = node(line, fields, graph.node_quantity)?;
+                let to = node(line, fields, graph.node_quantity)?;
+                let cost = field(line, fields, "arc cost")?;
+                graph.arcs.push((from, to, cost));
+            },
+            ("a", None) => return Err(DimacsError::MissingProblemLine { line }),
+            _ => return Err(unknown_line(line, kind, fields)),
+        }
+        Ok(())
+    })?;
+    let (graph, arc_quantity) = graph.ok_or(DimacsError::NoProblemLine)?;
+    check_count("arcs", arc_quantity, graph.arcs.len())?;
+    Ok(graph)
+}
+
+//challenge nodes are checked against the graph they are run on
+pub fn parse_challenge<R: BufRead>(reader: R, node_quantity: usize) -> Result<Challenge, DimacsError> {
+    let mut challenge: Option<(Challenge, usize)> = None;
+    for_each_line(reader, |line, kind, fields| {
+        match (kind, &mut challenge) {
+            ("p", None) => {
+                expect_problem(line, fields, "aux")?;
+                expect_problem(line, fields, "sp")?;
+                let problem = fields.next().unwrap_or_default().to_string();
+                let count = count(line, fields, "query count", COUNT_LIMIT)?;
+                let capacity = count.min(RESERVE_LIMIT);
+                challenge = match problem.as_str() {
+                    "ss" => Some((Challenge::Ss(Vec::with_capacity(capacity)), count)),
+                    "p2p" => Some((Challenge::P2p(Vec::with_capacity(capacity)), count)),
+                    _ => return Err(DimacsError::WrongProblem { line, expected: "ss or p2p", found: problem }),
+                };
+            },
+            ("p", Some(_)) => return Err(DimacsError::DuplicateProblemLine { line }),
+            ("s", Some((Challenge::Ss(sources), _))) => sources.push(node(line, fields, node_quantity)?),
+            ("q", Some((Challenge::P2p(queries), _))) => {
+                let from = node(line, fields, node_quantity)?;
+                let to = node(line, fields, node_quantity)?;
+                queries.push((from, to));
+            },
+            ("s" | "q", None) => return Err(DimacsError::MissingProblemLine { line }),
+            _ => return Err(unknown_line(line, kind, fields)),
+        }
+        Ok(())
+    })?;
+    let (challenge, count) = challenge.ok_or(DimacsError::NoProblemLine)?;
+    match &challenge {
+        Challenge::Ss(sources) => check_count("sources", count, sources.len())?,
+        Challenge::P2p(queries) => check_count("queries", count, queries.len())?,
+    }
+    Ok(challenge)
+}
+
+pub fn parse_coordinates<R: BufRead>(reader: R) -> Result<Coordinates, DimacsError> {
+    let mut node_quantity: Option<usize> = None;
+    //grown up to the highest id seen, the header count alone allocates nothing
+    let mut nodes: Vec<Option<(i64, i64)>> = Vec::new();
+    let mut found = 0;
+    for_each_line(reader, |line, kind, fields| {
+        match (kind, node_quantity) {
+            ("p", None) => {
+                expect_problem(line, fields, "aux")?;
+                expect_problem(line, fields, "sp")?;
+                expect_problem(line, fields, "co")?;
+                node_quantity = Some(count(line, fields, "node count", NODE_LIMIT)?);
+            },
+            ("p", Some(_)) => return Err(DimacsError::DuplicateProblemLine { line }),
+            ("v", Some(node_quantity)) => {
+                let id = node(line, fields, node_quantity)?;
+                let x = field(line, fields, "x coordinate")?;
+                let y = field(line, fields, "y coordinate")?;
+                if nodes.len() < id {
+                    nodes.resize(id, None);
+                }
+                if nodes[id - 1].replace((x, y)).is_some() {
+                    return Err(DimacsError::DuplicateNode { line, node: id });
+                }
+                found += 1;
+            },
+            ("v", None) => return Err(DimacsError::MissingProblemLine { line }),
+            _ => return Err(unknown_line(line, kind, fields)),
+        }
+        Ok(())
+    })?;
+    let node_quantity = node_quantity.ok_or(DimacsError::NoProblemLine)?;
+    check_count("coordinates", node_quantity, found)?;
+    //no duplicates and the right count, so every node is set and node n made the vector full length
+    Ok(Coordinates { coordinates: nodes.into_iter().flatten().collect() })
+}
+
+pub fn read_graph<P: AsRef<Path>>(path: P) -> Result<GraphFile, DimacsError> {
+    parse_graph(open(path.as_ref())?)
+}
+
+pub fn load_graph<P: AsRef<Path>>(path: P) -> Result<Graph, DimacsError> {
+    read_graph(path)?.to_graph()
+}
+
+pub fn read_signed_graph<P: AsRef<Path>>(path: P) -> Result<GraphFile<i64>, DimacsError> {
+    parse_signed_graph(open(path.as_ref())?)
+}
+
+pub fn read_challenge<P: AsRef<Path>>(path: P, node_quantity: usize) -> Result<Challenge, DimacsError> {
+    parse_challenge(open(path.as_ref())?, node_quantity)
+}
+
+pub fn read_coordinates<P: AsRef<Path>>(path: P) -> Result<Coordinates, DimacsError> {
+    parse_coordinates(open(path.as_ref())?)
+}
+
+pub(crate) fn open(path: &Path) -> Result<BufReader<File>, DimacsError> {
+    File::open(path)
+        .map(BufReader::new)
+        .map_err(|source| DimacsError::Io { path: path.display().to_string(), source })
+}
+
+//calls `handle` with (line number, first token, remaining tokens) for every non comment line
+pub(crate) fn for_each_line<R, F>(mut reader: R, mut handle: F) -> Result<(), DimacsError>
+where R: BufRead, F: FnMut(usize, &str, &mut SplitWhitespace) -> Result<(), DimacsError> {
+    let mut buffer = String::new();
+    let mut line = 0;
+    loop {
+        buffer.clear();
+        let read = reader
+            .read_line(&mut buffer)
+            .map_err(|source| DimacsError::Read { line: line + 1, source })?;
+        if read == 0 {
+            return Ok(());
+        }
+        line += 1;
+        let mut fields = buffer.split_whitespace();
+        match fields.next() {
+            None | Some("c") => continue,
+            Some(kind) => handle(line, kind, &mut fields)?,
+        }
+    }
+}
+
+pub(crate) fn field<T: FromStr>(line: usize, fields: &mut SplitWhitespace, name: &'static str) -> Result<T, DimacsError> {
+    let value = fields.next().ok_or(DimacsError::BadField { line, field: name, value: None })?;
+    value.parse().map_err(|_| DimacsError::BadField { line, field: name, value: Some(value.to_string()) })
+}
+
+//a count from a problem line, refused before anything is sized from it when it cannot be stored
+pub(crate) fn count(line: usize, fields: &mut SplitWhitespace, name: &'static str, limit: usize) -> Result<usize, DimacsError> {
+    let count = field(line, fields, name)?;
+    if count > limit {
+        return Err(DimacsError::TooLarge { what: name, value: count, limit });
+    }
+    Ok(count)
+}
+
+pub(crate) fn node(line: usize, fields: &mut SplitWhitespace, node_quantity: usize) -> Result<usize, DimacsError> {
+    let node = field(line, fields, "node id")?;
+    if node == 0 || node > node_quantity {
+        return Err(DimacsError::NodeOutOfRange { line, node, node_quantity });
+    }
+    Ok(node)
+}
+
+pub(crate) fn expect_problem(line: usize, fields: &mut SplitWhitespace, expected: &'static str) -> Result<(), DimacsError> {
+    match fields.next() {
+        Some(found) if found == expected => Ok(()),
+        found => Err(DimacsError::WrongProblem { line, expected, found: found.unwrap_or_default().to_string() }),
+    }
+}
+
+pub(crate) fn unknown_line(line: usize, kind: &str, fields: &mut SplitWhitespace) -> DimacsError {
+    let rest: Vec<&str> = fields.collect();
+    DimacsError::UnknownLine { line, content: format!("{kind} {}", rest.join(" ")).trim_end().to_string() }
+}
+
+pub(crate) fn check_count(what: &'static str, expected: usize, found: usize) -> Result<(), DimacsError> {
+    if expected != found {
+        return Err(DimacsError::CountMismatch { what, expected, found });
+    }
+    Ok(())
+}
+
+//Common part of the 9th DIMACS challenge .ss.res and .p2p.res files
+#[derive(Clone, Debug, PartialEq)]
+pub struct ResultHeader {
+    pub algorithm: String,
+    pub graph_file: String,
+    pub challenge_file: String,
+    pub node_quantity: usize,
+    pub arc_quantity: usize,
+    pub min_weight: usize,
+    pub max_weight: usize,
+}
+
+impl ResultHeader {
+    //only the file names are kept, like in the reference result files
+    pub fn new(algorithm: Algorithm, graph_file: &str, challenge_file: &str, graph: &Graph) -> Self {
+        ResultHeader {
+            algorithm: algorithm.name().to_string(),
+            graph_file: file_name(graph_file),
+            challenge_file: file_name(challenge_file),
+            node_quantity: graph.node_quantity(),
+            arc_quantity: graph.arc_quantity(),
+            min_weight: graph.min_weight(),
+            max_weight: graph.max_weight(),
+        }
+    }
+
+    fn write<W: Write>(&self, out: &mut W, challenge: &str) -> io::Result<()> {
+        writeln!(out, "p res sp {challenge} {}", self.algorithm)?;
+        writeln!(out, "f {} {}", self.graph_file, self.challenge_file)?;
+        writeln!(out, "g {} {} {} {}", self.node_quantity, self.arc_quantity, self.min_weight, self.max_weight)
+    }
+}
+
+//average time of one single source search in milliseconds
+pub fn write_ss_result<W: Write>(out: &mut W, header: &ResultHeader, average_time_ms: f64) -> io::Result<()> {
+    header.write(out, "ss")?;
+    writeln!(out, "t {average_time_ms:.3}")
+}
+
+//average time of one query in milliseconds, then one d line per query in challenge order, unreachable targets get -1
+pub fn write_p2p_result<W: Write>(out: &mut W, header: &ResultHeader, average_time_ms: f64, queries: &[(usize, usize, Option<usize>)]) -> io::Result<()> {
+    header.write(out, "p2p")?;
+    writeln!(out, "t {average_time_ms:.3}")?;
+    for (src, dst, distance) in queries {
+        match distance {
+            Some(distance) => writeln!(out, "d {src} {dst} {distance}")?,
+            None => writeln!(out, "d {src} {dst} -1")?,
+        }
+    }
+    Ok(())
+}
+
+fn file_name(path: &str) -> String {
+    Path::new(path)
+        .file_name()
+        .map_or(path.to_string(), |name| name.to_string_lossy().into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use crate::dimacs::*;
+
+    fn graph() -> Graph {
+        let mut graph = Graph::new(3);
+        graph.add_edge(1, 2, 4);
+        graph.add_edge(2, 3, 7);
+        graph.add_edge(2, 1, 2);
+        graph
+    }
+
+    #[test]
+    fn write_ss_result_test() {
+        let header = ResultHeader::new(Algorithm::Dial, "/data/USA-road-d.NY.gr", "USA-road-d.NY.ss", &graph());
+        let mut out: Vec<u8> = Vec::new();
+        write_ss_result(&mut out, &header, 12.5).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), "p res sp ss dial\nf USA-road-d.NY.gr USA-road-d.NY.ss\ng 3 3 2 7\nt 12.500\n");
+    }
+
+    #[test]
+    fn write_p2p_result_test() {
+        let graph = graph();
+        let header = ResultHeader::new(Algorithm::Classic, "a.gr", "a.p2p", &graph);
+        let queries: Vec<(usize, usize, Option<usize>)> = [(1, 3), (3, 1)]
+            .iter()
+            .map(|&(s, t)| (s, t, graph.djikstra_classic_p2p(s, t)))
+            .collect();
+        let mut out: Vec<u8> = Vec::new();
+        write_p2p_result(&mut out, &header, 0.25, &queries).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), "p res sp p2p dijkstra\nf a.gr a.p2p\ng 3 3 2 7\nt 0.250\nd 1 3 11\nd 3 1 -1\n");
+    }
+
+    #[test]
+    fn parse_graph_test() {
+        let input = "c 9th DIMACS\np sp 3 3\nc arcs\na 1 2 4\na 2 3 7\n\na 2 1 2\n";
+        let graph = parse_graph(Cursor::new(input)).unwrap();
+        assert_eq!(graph, GraphFile { node_quantity: 3, arcs: vec![(1, 2, 4), (2, 3, 7), (2, 1, 2)] });
+        assert_eq!(graph.to_graph().unwrap().djikstra_classic_p2p(1, 3), Some(11));
+        let signed = parse_signed_graph(Cursor::new("p sp 2 2\na 1 2 -4\na 2 1 5\n")).unwrap();
+        assert_eq!(signed.arcs, vec![(1, 2, -4), (2, 1, 5)]);
+        assert_eq!(signed.to_signed_graph().bellman_ford(1).unwrap().distance(2), Some(-4));
+        assert_eq!(parse_graph(Cursor::new("p sp 2 1\na 1 2 -4\n")).unwrap_err().to_string(), "line 2: invalid arc cost '-4'");
+    }
+
+    #[test]
+    fn parse_graph_errors_test() {
+        let error = |input: &str| parse_graph(Cursor::new(input.to_string())).unwrap_err().to_string();
+        assert_eq!(error("a 1 2 3\n"), "line 1: data before the problem line");
+        assert_eq!(error("p sp 2 1\na 1 3 1\n"), "line 2: node 3 out of range 1..=2");
+        assert_eq!(error("p sp 2 1\na 1 2 x\n"), "line 2: invalid arc cost 'x'");
+        assert_eq!(error("p sp 2 1\na 1 2\n"), "line 2: missing arc cost");
+        assert_eq!(error("p sp 2 2\na 1 2 1\n"), "problem line declares 2 arcs, file has 1");
+        assert_eq!(error("p sp 2 0\np sp 2 0\n"), "line 2: second problem line");
+        assert_eq!(error("p max 2 0\n"), "line 1: expected a 'sp' problem, found 'max'");
+        assert_eq!(error("p sp 2 0\nx 1\n"), "line 2: unknown line 'x 1'");
+        assert_eq!(error("p sp 5 18446744073709551615\n"), "arc count 18446744073709551615 above the limit of 4294967295");
+        assert_eq!(error("p sp 4294967297 0\n"), "node count 4294967297 above the limit of 4294967296");
+        //a count that fits is only checked against the lines that follow
+        assert_eq!(error("p sp 5 4294967295\na 1 2 3\n"), "problem line declares 4294967295 arcs, file has 1");
+        assert!(matches!(read_graph("/nonexistent/graph.gr"), Err(DimacsError::Io { .. })));
+    }
+
+    #[test]
+    fn parse_challenge_test() {
+        let ss = parse_challenge(Cursor::new("c\np aux sp ss 2\ns 1\ns 3\n"), 3).unwrap();
+        assert_eq!(ss, Challenge::Ss(vec![1, 3]));
+        let p2p = parse_challenge(Cursor::new("p aux sp p2p 1\nq 1 3\n"), 3).unwrap();
+        assert_eq!(p2p, Challenge::P2p(vec![(1, 3)]));
+        assert!(matches!(parse_challenge(Cursor::new("p aux sp ss 1\ns 4\n"), 3), Err(DimacsError::NodeOutOfRange { line: 2, node: 4, .. })));
+        assert!(matches!(parse_challenge(Cursor::new("p aux sp ss 1\nq 1 2\n"), 3), Err(DimacsError::UnknownLine { line: 2, .. })));
+        assert!(matches!(parse_challenge(Cursor::new("p aux sp ss 2\ns 1\n"), 3), Err(DimacsError::CountMismatch { .. })));
+        assert!(matches!(parse_challenge(Cursor::new("p aux sp p2p 99999999999\n"), 3), Err(DimacsError::TooLarge { .. })));
+    }
+
+    #[test]
+    fn parse_coordinates_test() {
+        let coordinates = parse_coordinates(Cursor::new("p aux sp co 2\nv 2 -73530767 41085396\nv 1 -73530538 41086098\n")).unwrap();
+        assert_eq!(coordinates.coordinates, vec![(-73530538, 41086098), (-73530767, 41085396)]);
+        assert!(matches!(parse_coordinates(Cursor::new("p aux sp co 2\nv 1 0 0\nv 1 0 0\n")), Err(DimacsError::DuplicateNode { line: 3, node: 1 })));
+        assert!(matches!(parse_coordinates(Cursor::new("p aux sp co 2\nv 1 0 0\n")), Err(DimacsError::CountMismatch { .. })));
+        assert!(matches!(parse_coordinates(Cursor::new("p aux sp co 4294967296\nv 1 0 0\n")), Err(DimacsError::CountMismatch { .. })));
+    }
+}
@@ -0,0 +1,230 @@
+use std::fs::File;
+use std::io::{self, BufWriter, ErrorKind, Write};
+use std::ops::RangeInclusive;
+use std::path::{Path, PathBuf};
+
+use rand::prelude::*;
+use rand_pcg::Pcg64;
+
+use crate::dimacs::{Challenge, GraphFile};
+
+//log2 of the node count of the families that vary C, as in the 9th DIMACS challenge
+pub const C_FAMILY_NODES_LOG2: u32 = 20;
+//rows of the long grids
+pub const LONG_GRID_WIDTH: usize = 16;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Shape {
+    //Hamiltonian cycle through a random permutation plus random arcs up to 4 per node, like SPRAND
+    Random4 { node_quantity: usize },
+    //SPGRID style grid of x layers with y nodes: every layer is a cycle with arcs both ways and every node
+    //is linked both ways to the same position in the next layer
+    Grid { x: usize, y: usize },
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Family {
+    //n = 2^e, C = n
+    Random4N,
+    //n = 2^20, C = 4^e
+    Random4C,
+    //x = y = 2^(e / 2), C = n
+    SquareN,
+    //x = y = 2^10, C = 4^e
+    SquareC,
+    //y = 16, x = 2^e / 16, C = n
+    LongN,
+    //y = 16, x = 2^16, C = 4^e
+    LongC,
+}
+
+impl Family {
+    pub const ALL: [Family; 6] = [Family::Random4N, Family::Random4C, Family::SquareN, Family::SquareC, Family::LongN, Family::LongC];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Family::Random4N => "Random4-n",
+            Family::Random4C => "Random4-C",
+            Family::SquareN => "Square-n",
+            Family::SquareC => "Square-C",
+            Family::LongN => "Long-n",
+            Family::LongC => "Long-C",
+        }
+    }
+
+    //exponents whose instances have n = 2^e (or C = 4^e) exactly and fit in the u32 ids, costs and arc
+    //counts of the graph, about 4n arcs for every shape
+    pub fn exponents(&self) -> RangeInclusive<u32> {
+        match self {
+            Family::Random4N => 0..=29,
+            Family::SquareN => 0..=28,
+            Family::LongN => LONG_GRID_WIDTH.ilog2()..=29,
+            Family::Random4C | Family::SquareC | Family::LongC => 0..=15,
+        }
+    }
+
+    //e is log2 n for the -n families and log4 C for the -C ones, Square-n also needs an even e
+    pub fn spec(&self, e: u32) -> io::Result<Spec> {
+        if !self.exponents().contains(&e) || (*self == Family::SquareN && e % 2 == 1) {
+            let (low, high) = self.exponents().into_inner();
+            let parity = if *self == Family::SquareN { "an even number " } else { "" };
+            let message = format!("{} needs {parity}e in {low}..={high}, got {e}", self.name());
+            return Err(io::Error::new(ErrorKind::InvalidInput, message));
+        }
+        let n = 1usize << e;
+        let side = 1usize << (e / 2);
+        let c = 1usize << (2 * e);
+        let fixed = 1usize << C_FAMILY_NODES_LOG2;
+        let fixed_side = 1usize << (C_FAMILY_NODES_LOG2 / 2);
+        let (shape, max_weight) = match self {
+            Family::Random4N => (Shape::Random4 { node_quantity: n }, n),
+            Family::Random4C => (Shape::Random4 { node_quantity: fixed }, c),
+            Family::SquareN => (Shape::Grid { x: side, y: side }, side * side),
+            Family::SquareC => (Shape::Grid { x: fixed_side, y: fixed_side }, c),
+            Family::LongN => (Shape::Grid { x: n / LONG_GRID_WIDTH, y: LONG_GRID_WIDTH }, n),
+            Family::LongC => (Shape::Grid { x: fixed / LONG_GRID_WIDTH, y: LONG_GRID_WIDTH }, c),
+        };
+        Ok(Spec { shape, max_weight })
+    }
+}
+
+//costs are uniform in 0..=max_weight
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Spec {
+    pub shape: Shape,
+    pub max_weight: usize,
+}
+
+//a graph with its challenges, everything drawn from one seeded generator
+#[derive(Clone, Debug, PartialEq)]
+pub struct Instance {
+    pub graph: GraphFile,
+    pub sources: Challenge,
+    pub queries: Challenge,
+}
+
+impl Spec {
+    pub fn node_quantity(&self) -> usize {
+        match self.shape {
+            Shape::Random4 { node_quantity } => node_quantity,
+            Shape::Grid { x, y } => x * y,
+        }
+    }
+
+    pub fn generate(&self, seed: u64, source_quantity: usize, query_quantity: usize) -> Instance {
+        let mut rng = Pcg64::seed_from_u64(seed);
+        let n = self.node_quantity();
+        let mut arcs = Vec::new();
+        let mut arc = |rng: &mut Pcg64, from: usize, to: usize| arcs.push((from + 1, to + 1, rng.gen_range(0..=self.max_weight)));
+        match self.shape {
+            Shape::Random4 { node_quantity } => {
+                let mut cycle: Vec<usize> = (0..node_quantity).collect();
+                cycle.shuffle(&mut rng);
+                for i in 0..node_quantity {
+                    arc(&mut rng, cycle[i], cycle[(i + 1) % node_quantity]);
+                }
+                for _ in node_quantity..4 * node_quantity {
+                    let from = rng.gen_range(0..node_quantity);
+                    let to = rng.gen_range(0..node_quantity);
+                    arc(&mut rng, from, to);
+                }
+            },
+            Shape::Grid { x, y } => {
+                let id = |layer: usize, row: usize| layer * y + row;
+                for layer in 0..x {
+                    for row in 0..y {
+                        if y > 1 {
+                            arc(&mut rng, id(layer, row), id(layer, (row + 1) % y));
+                            arc(&mut rng, id(layer, (row + 1) % y), id(layer, row));
+                        }
+                        if layer + 1 < x {
+                            arc(&mut rng, id(layer, row), id(layer + 1, row));
+                            arc(&mut rng, id(layer + 1, row), id(layer, row));
+                        }
+                    }
+                }
+            },
+        }
+        let sources = (0..source_quantity).map(|_| rng.gen_range(1..=n)).collect();
+        let queries = (0..query_quantity).map(|_| (rng.gen_range(1..=n), rng.gen_range(1..=n))).collect();
+        Instance {
+            graph: GraphFile { node_quantity: n, arcs },
+            sources: Challenge::Ss(sources),
+            queries: Challenge::P2p(queries),
+        }
+    }
+}
+
+impl Instance {
+    //<prefix>.gr, <prefix>.ss and <prefix>.p2p, returns the three paths
+    pub fn save<P: AsRef<Path>>(&self, prefix: P, comment: &str) -> io::Result<[PathBuf; 3]> {
+        let prefix = prefix.as_ref().display().to_string();
+        let paths = [".gr", ".ss", ".p2p"].map(|extension| PathBuf::from(format!("{prefix}{extension}")));
+        let mut graph = BufWriter::new(File::create(&paths[0])?);
+        writeln!(graph, "c {comment}")?;
+        self.graph.write(&mut graph)?;
+        graph.flush()?;
+        for (challenge, path) in [&self.sources, &self.queries].into_iter().zip(&paths[1..]) {
+            let mut out = BufWriter::new(File::create(path)?);
+            writeln!(out, "c {comment}")?;
+            challenge.write(&mut out)?;
+            out.flush()?;
+        }
+        Ok(paths)
+    }
+}
+
+//writes <directory>/<family>.<e>.<seed>.{gr,ss,p2p}, the naming of the challenge files
+pub fn generate_family<P: AsRef<Path>>(directory: P, family: Family, e: u32, seed: u64, source_quantity: usize, query_quantity: usize) -> io::Result<[PathBuf; 3]> {
+    let instance = family.spec(e)?.generate(seed, source_quantity, query_quantity);
+    let prefix = directory.as_ref().join(format!("{}.{e}.{seed}", family.name()));
+    instance.save(prefix, &format!("{} e = {e} seed = {seed}", family.name()))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::dimacs::{read_challenge, read_graph};
+    use crate::generators::*;
+
+    #[test]
+    fn generators_test() {
+        let random = Spec { shape: Shape::Random4 { node_quantity: 100 }, max_weight: 50 };
+        let instance = random.generate(7, 5, 20);
+        assert_eq!(instance, random.generate(7, 5, 20));
+        assert_ne!(instance, random.generate(8, 5, 20));
+        assert_eq!(instance.graph.arcs.len(), 400);
+        assert!(instance.graph.arcs.iter().all(|(_, _, cost)| *cost <= 50));
+        //the Hamiltonian cycle reaches everything
+        let graph = instance.graph.to_graph().unwrap();
+        assert!(graph.djikstra_classic_ss(1).iter().all(Option::is_some));
+
+        let grid = Spec { shape: Shape::Grid { x: 6, y: 4 }, max_weight: 9 }.generate(1, 3, 3);
+        assert_eq!(grid.graph.node_quantity, 24);
+        assert_eq!(grid.graph.arcs.len(), 2 * 6 * 4 + 2 * 5 * 4);
+        assert!(grid.graph.to_graph().unwrap().djikstra_classic_ss(24).iter().all(Option::is_some));
+
+        assert_eq!(Family::SquareN.spec(10).unwrap().shape, Shape::Grid { x: 32, y: 32 });
+        assert_eq!(Family::LongN.spec(10).unwrap().shape, Shape::Grid { x: 64, y: 16 });
+        assert_eq!(Family::Random4C.spec(3).unwrap().max_weight, 64);
+        assert_eq!(Family::Random4N.spec(12).unwrap().node_quantity(), 4096);
+        assert_eq!(Family::Random4C.spec(15).unwrap().max_weight, 1 << 30);
+        for (family, e) in [(Family::Random4C, 16), (Family::Random4N, 32), (Family::SquareN, 9), (Family::LongN, 3)] {
+            assert_eq!(family.spec(e).unwrap_err().kind(), ErrorKind::InvalidInput);
+        }
+    }
+
+    #[test]
+    fn generate_family_test() {
+        let directory = std::env::temp_dir().join(format!("lista_3_generators_test_{}", std::process::id()));
+        std::fs::create_dir_all(&directory).unwrap();
+        for family in [Family::Random4N, Family::SquareN, Family::LongN] {
+            let [graph, ss, p2p] = generate_family(&directory, family, 8, 3, 4, 10).unwrap();
+            assert!(graph.ends_with(format!("{}.8.3.gr", family.name())));
+            let graph = read_graph(&graph).unwrap();
+            assert_eq!(graph.node_quantity, 256);
+            assert!(matches!(read_challenge(&ss, 256).unwrap(), Challenge::Ss(sources) if sources.len() == 4));
+            assert!(matches!(read_challenge(&p2p, 256).unwrap(), Challenge::P2p(queries) if queries.len() == 10));
+        }
+        std::fs::remove_dir_all(&directory).unwrap();
+    }
+}
@@ -1,29 +1,29 @@
-use std::collections::{BinaryHeap, HashSet, VecDeque};
 use std::cmp::Ordering;
+use std::sync::OnceLock;
 use std::usize;
 
-#[derive(Clone, Debug)]
-struct RadixBucket {
-    v_list: VecDeque<usize>,
-    range_a: usize,
-    range_b: usize,
-}
-
-impl RadixBucket {
-    fn generate_sequence(n: u32) -> Vec<(usize, usize)> {
-        let mut sequence = Vec::new();
-        let mut prev_value = 0;
-    
-        for i in 0..n {
-            let current_value = 2usize.pow(i);
-            let current_tuple = (prev_value, current_value - 1);
-            sequence.push(current_tuple);
-            prev_value = current_value;
-        }
-        sequence
-    }
+use csr::Csr;
+use dimacs::DimacsError;
+use queue::{DialBuckets, LazyBinaryHeap};
+use radix_heap::RadixHeap;
 
-}
+pub mod alt;
+pub mod apsp;
+pub mod astar;
+pub mod batch;
+pub mod bench;
+pub mod bidirectional;
+pub mod cache;
+pub mod ch;
+pub mod csr;
+pub mod delta_stepping;
+pub mod dimacs;
+pub mod generators;
+pub mod queue;
+pub mod radix_heap;
+pub mod signed;
+pub mod stats;
+pub mod verify;
 
 #[derive(Clone, Eq, Hash, PartialEq, Debug)]
 pub struct SearchNode{
@@ -43,224 +43,235 @@ impl PartialOrd for SearchNode {
     }
 }
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Algorithm {
+    Classic,
+    Dial,
+    Radix,
+}
+
+impl Algorithm {
+    pub const ALL: [Algorithm; 3] = [Algorithm::Classic, Algorithm::Dial, Algorithm::Radix];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Algorithm::Classic => "dijkstra",
+            Algorithm::Dial => "dial",
+            Algorithm::Radix => "radixheap",
+        }
+    }
+}
+
+//distances and predecessors are indexed by node - 1, predecessors hold node ids
+#[derive(Clone, Debug, PartialEq)]
+pub struct ShortestPathTree {
+    pub source: usize,
+    pub distances: Vec<Option<usize>>,
+    pub predecessors: Vec<Option<usize>>,
+}
+
+impl ShortestPathTree {
+    fn new(source: usize, node_quantity: usize) -> Self {
+        let mut distances = vec![None; node_quantity];
+        distances[source - 1] = Some(0);
+        ShortestPathTree { source, distances, predecessors: vec![None; node_quantity] }
+    }
+
+    pub fn distance(&self, dst: usize) -> Option<usize> {
+        self.distances[dst - 1]
+    }
+
+    //nodes from the source to dst, None if dst is unreachable
+    pub fn path(&self, dst: usize) -> Option<Vec<usize>> {
+        self.distances[dst - 1]?;
+        let mut path = vec![dst];
+        let mut node = dst;
+        while let Some(previous) = self.predecessors[node - 1] {
+            path.push(previous);
+            node = previous;
+        }
+        path.reverse();
+        Some(path)
+    }
+}
+
 #[derive(Clone, Debug, Default)]
 pub struct Graph{
     node_quantity: usize,
     max_weight: usize,
-    adj: Vec<HashSet<(usize, usize)>>
+    adj: Csr,
+    //incoming arcs as (from, cost), built by the first search that runs backwards from the goal
+    radj: OnceLock<Csr>,
 }
 
 impl Graph {
     pub fn new(node_quantity: usize) -> Self{
-        let adj: Vec<HashSet<(usize, usize)>> = vec![HashSet::new(); node_quantity];
         Graph {
             node_quantity,
             max_weight: 0,
-            adj 
+            adj: Csr::new(node_quantity),
+            radj: OnceLock::new(),
         }
     }
+    //the whole graph at once from (from, to, cost) arcs, as the DIMACS parser reads them. Node ids,
+    //costs and the arc count have to fit in u32
+    pub fn from_arcs(node_quantity: usize, arcs: &[(usize, usize, usize)]) -> Result<Self, DimacsError> {
+        let limit = dimacs::COUNT_LIMIT;
+        if node_quantity > dimacs::NODE_LIMIT {
+            return Err(DimacsError::TooLarge { what: "node count", value: node_quantity, limit: dimacs::NODE_LIMIT });
+        }
+        if arcs.len() > limit {
+            return Err(DimacsError::TooLarge { what: "arc count", value: arcs.len(), limit });
+        }
+        for (index, &(from, to, _)) in arcs.iter().enumerate() {
+            if let Some(node) = [from, to].into_iter().find(|node| *node == 0 || *node > node_quantity) {
+                return Err(DimacsError::ArcNodeOutOfRange { arc: index + 1, node, node_quantity });
+            }
+        }
+        let max_weight = arcs.iter().map(|(_, _, cost)| *cost).max().unwrap_or(0);
+        if max_weight > limit {
+            return Err(DimacsError::TooLarge { what: "arc cost", value: max_weight, limit });
+        }
+        Ok(Graph {
+            node_quantity,
+            max_weight,
+            adj: Csr::from_arcs(node_quantity, arcs.iter().map(|&(from, to, cost)| (from - 1, to - 1, cost))),
+            radj: OnceLock::new(),
+        })
+    }
+    //every add_edge rewrites the arc arrays, large graphs should come from from_arcs
     pub fn add_edge(&mut self, from: usize, to: usize, cost: usize) {
         if cost > self.max_weight{
             self.max_weight = cost;
         }
-        self.adj[from - 1].insert((to - 1, cost));
-    }
-    pub fn djikstra_classic_p2p(&mut self, start: usize, goal: usize) -> Option<usize> {
-        let start = start - 1;
-        let goal = goal - 1;
-
-        if start == goal {
-            return Some(0); 
-        }
-        let mut queue: BinaryHeap<SearchNode> = BinaryHeap::new();
-        let mut visited = vec![false; self.node_quantity];
-        visited[start] = true;
-        for (id, distance) in &self.adj[start] {
-            queue.push(SearchNode{id: *id,  distance: *distance});
+        self.adj.insert(from - 1, to - 1, cost);
+        if let Some(radj) = self.radj.get_mut() {
+            radj.insert(to - 1, from - 1, cost);
         }
-        while let Some(current_node) = queue.pop() {
-            visited[current_node.id] = true;
-            if current_node.id == goal{
-                return Some(current_node.distance);
-            }
-            let d = current_node.distance;
-            for (id, dist) in &self.adj[current_node.id] {
-                if !visited[*id]{
-                    queue.push(SearchNode { id: *id, distance: (dist + d)});
-                }
-            }
+    }
+    pub fn node_quantity(&self) -> usize {
+        self.node_quantity
+    }
+    pub fn arc_quantity(&self) -> usize {
+        self.adj.arc_quantity()
+    }
+    //(to, cost) of the arcs leaving node, both 0-based
+    pub(crate) fn arcs(&self, node: usize) -> impl Iterator<Item = (usize, usize)> + '_ {
+        self.adj.arcs(node)
+    }
+    //the incoming arcs, transposed once and kept for later searches
+    pub(crate) fn radj(&self) -> &Csr {
+        self.radj.get_or_init(|| self.adj.transpose())
+    }
+    //the same nodes with every arc turned around
+    pub fn reversed(&self) -> Graph {
+        Graph {
+            node_quantity: self.node_quantity,
+            max_weight: self.max_weight,
+            adj: self.radj().clone(),
+            radj: OnceLock::new(),
         }
-        None
-    }
-    pub fn djikstra_classic_ss(&mut self, src: usize) -> Vec<Option<usize>>  {
-        let src = src - 1;
-        let mut distances = vec![None; self.node_quantity];
-        distances[src] = Some(0);
-        let mut heap = BinaryHeap::new();
-        heap.push((0, src));
-        while let Some((distance, node)) = heap.pop() {
-            if let Some(current_distance) = distances[node] {
-                if distance > current_distance {
-                    continue;
-                }
-            }
-            for (neighbor, weight) in &self.adj[node] {
-                let new_distance = distance + weight;
-                if distances[*neighbor].map_or(true, |d| new_distance < d) {
-                    distances[*neighbor] = Some(new_distance);
-                    heap.push((new_distance, *neighbor));
-                }
-            }
+    }
+    pub fn max_weight(&self) -> usize {
+        self.max_weight
+    }
+    pub fn min_weight(&self) -> usize {
+        self.adj.costs().min().unwrap_or(0)
+    }
+    //total cost of walking the path, the cheapest arc is taken between consecutive nodes
+    pub fn path_length(&self, path: &[usize]) -> Option<usize> {
+        let mut length = 0;
+        for pair in path.windows(2) {
+            length += self.arcs(pair[0] - 1)
+                .filter(|(to, _)| *to == pair[1] - 1)
+                .map(|(_, cost)| cost)
+                .min()?;
         }
-        distances
+        Some(length)
     }
-    pub fn dial_p2p(&mut self, start: usize, goal: usize) -> Option<usize> {
-        if start == goal{
-            return Some(0);
+    pub fn shortest_path_tree(&self, algorithm: Algorithm, src: usize) -> ShortestPathTree {
+        match algorithm {
+            Algorithm::Classic => self.djikstra_classic_tree(src),
+            Algorithm::Dial => self.dial_tree(src),
+            Algorithm::Radix => self.radix_tree(src),
         }
-        let goal = goal - 1;
-        self.dial_ss(start)[goal]
     }
-    pub fn dial_ss(&mut self, src: usize)  -> Vec<Option<usize>> {
-        let src = src - 1;
-
-        let mut distances = vec![None; self.node_quantity];
-        let max_buckets = self.node_quantity * self.max_weight;
-        let mut buckets: Vec<Vec<usize>> = vec![Vec::new(); max_buckets];
-        distances[src] = Some(0);
-        buckets[0].push(src);
-        let mut bucket_idx: usize = 0;
-        loop {
-            while bucket_idx < max_buckets && buckets[bucket_idx].is_empty() {
-                bucket_idx += 1;
-            }
-            if bucket_idx >= max_buckets {
-               break; 
-            }
-            let v = *buckets[bucket_idx].first().unwrap();
-            buckets[bucket_idx].retain(|x| *x != v);
-            for (u, w) in &self.adj[v] {
-                let alt_dist = distances[v].unwrap_or(usize::MAX) + w;
-                let curr_dist = distances[*u].unwrap_or(usize::MAX);
-                if alt_dist < curr_dist {
-                    if curr_dist != usize::MAX {
-                        buckets[alt_dist].retain(|x| *x != *u);
-                    }
-                    buckets[alt_dist].push(*u);
-                    distances[*u] = Some(alt_dist);
-                }
-            }
-        }        
-        distances
+    //(distance, nodes from src to dst)
+    pub fn path(&self, algorithm: Algorithm, src: usize, dst: usize) -> Option<(usize, Vec<usize>)> {
+        let tree = match algorithm {
+            Algorithm::Classic => self.djikstra_classic_p2p_tree(src, dst),
+            Algorithm::Dial => self.dial_search(src, Some(dst)),
+            Algorithm::Radix => self.radix_search(src, Some(dst)),
+        };
+        Some((tree.distance(dst)?, tree.path(dst)?))
     }
-    pub fn radix_p2p(&mut self, start: usize, goal: usize) -> Option<usize> {
-        if start == goal{
-            return Some(0);
+    pub fn p2p(&self, algorithm: Algorithm, start: usize, goal: usize) -> Option<usize> {
+        match algorithm {
+            Algorithm::Classic => self.djikstra_classic_p2p(start, goal),
+            Algorithm::Dial => self.dial_p2p(start, goal),
+            Algorithm::Radix => self.radix_p2p(start, goal),
         }
-        let goal = goal - 1;
-        self.radix_ss(start)[goal]
+    }
+    pub fn djikstra_classic_p2p(&self, start: usize, goal: usize) -> Option<usize> {
+        self.djikstra_classic_p2p_tree(start, goal).distance(goal)
+    }
+    //stops as soon as the goal is settled, the tree is only complete on the way to it
+    fn djikstra_classic_p2p_tree(&self, start: usize, goal: usize) -> ShortestPathTree {
+        self.dijkstra::<LazyBinaryHeap>(start, Some(goal))
+    }
+    pub fn djikstra_classic_ss(&self, src: usize) -> Vec<Option<usize>>  {
+        self.djikstra_classic_tree(src).distances
+    }
+    pub fn djikstra_classic_tree(&self, src: usize) -> ShortestPathTree {
+        self.dijkstra::<LazyBinaryHeap>(src, None)
+    }
+    pub fn dial_p2p(&self, start: usize, goal: usize) -> Option<usize> {
+        self.dial_search(start, Some(goal)).distance(goal)
+    }
+    pub fn dial_ss(&self, src: usize)  -> Vec<Option<usize>> {
+        self.dial_tree(src).distances
+    }
+    pub fn dial_tree(&self, src: usize) -> ShortestPathTree {
+        self.dial_search(src, None)
+    }
+    fn dial_search(&self, src: usize, goal: Option<usize>) -> ShortestPathTree {
+        self.dijkstra::<DialBuckets>(src, goal)
+    }
+    pub fn radix_p2p(&self, start: usize, goal: usize) -> Option<usize> {
+        self.radix_search(start, Some(goal)).distance(goal)
     }
     pub fn radix_ss(&self, src: usize) -> Vec<Option<usize>> {
-        let src = src - 1;
-        let mut dist: Vec<Option<usize>> = vec![None; self.node_quantity];
-        dist[src] = Some(0);
-
-        let no_buckets = (((self.max_weight * self.node_quantity) as f64).log2().ceil() + 1_f64) as usize;
-        let mut buckets: Vec<RadixBucket> = Vec::with_capacity(no_buckets as usize);
-        let bucket_seq = RadixBucket::generate_sequence(no_buckets as u32);
-        //println!("{:?}", bucket_seq);
-        for rang in bucket_seq {
-            buckets.push(RadixBucket { v_list: VecDeque::new(), range_a: rang.0, range_b: rang.1 })
-        }
-        //println!("{:?}", buckets);
-        buckets[0].v_list.push_front(src);
-        
-        let mut idx;
-        loop {
-            //choosing the node
-            idx = 0;
-            while idx < buckets.len() && buckets[idx].v_list.len() == 0 {
-                idx += 1;
-            }
-            if idx == buckets.len(){
-                break;
-            }
-
-            let mut u = *buckets[idx].v_list.front().unwrap();
-            //ckecking if is alone in bucket
-            if buckets[idx].v_list.len() == 1 {
-                buckets[idx].v_list.clear();
-            } else {
-                let mut minv = 0;
-                let mut mindist = usize::MAX;
+        self.radix_tree(src).distances
+    }
+    pub fn radix_tree(&self, src: usize) -> ShortestPathTree {
+        self.radix_search(src, None)
+    }
+    fn radix_search(&self, src: usize, goal: Option<usize>) -> ShortestPathTree {
+        self.dijkstra::<RadixHeap>(src, goal)
+    }
+}
 
-                for v in &buckets[idx].v_list {
-                    if  dist[*v].unwrap_or(usize::MAX) < mindist {
-                        mindist = dist[*v].unwrap_or(usize::MAX);
-                        minv = *v;
-                    }
-                }
-                u = minv;
-                
-                println!("{minv}");
-                println!("{idx}");
-                //resize
-                let r = RadixBucket::generate_sequence(idx as u32);
-                for i in 0..idx {
-                    buckets[i].range_a = r[i].0 + mindist;
-                    buckets[i].range_b = r[i].1 + mindist;
-                    println!()
-                }
-                if idx != 0 {
-                    buckets[idx -1].range_b = buckets[idx].range_b;
-                }
-                //repopulate
-                for v in buckets[idx].v_list.clone() {
-                    for i in 0..idx {
-                        if dist[v].unwrap() >= buckets[i].range_a && dist[v].unwrap() <= buckets[i].range_b {
-                            buckets[i].v_list.push_front(v);
-                            break;
-                        }
-                    }
-                }
-                buckets[idx].range_a = 0;
-                buckets[idx].range_b = 0;
-                buckets[idx].v_list.clear();
-            }
 
-            for (v, weight) in &self.adj[u] {
-                let dv = dist[*v].unwrap_or(usize::MAX);
-                let du = dist[u].unwrap();
-                if dv > du + weight {
-                    if dv != usize::MAX {
-                        let mut tmp = 0;
-                        while !(buckets[tmp].range_a <= dv && buckets[tmp].range_b >= dv) {
-                            tmp += 1;
-                        }
-                        for (i, j) in buckets[tmp].v_list.iter().enumerate() {
-                            if j == v {
-                                buckets[tmp].v_list.remove(i);
-                                break;
-                            }
-                        }
-                    }
-                    let mut b = 0;
-                    //println!("{}", du + weight);
-                    while b < buckets.len() && !(buckets[b].range_a <= du + weight && buckets[b].range_b >= du + weight) {
-                        //println!("{}, {}", buckets[b].range_a, buckets[b].range_b);
-                        b += 1;
-                    }
-                    dist[*v] = Some(du + weight);
-                    buckets[b].v_list.push_front(*v);
-                }
-            }
-            //println!("{:?}", dist); 
-        }
-        dist
+//arbitrary digraph with parallel arcs, loops and zero costs, the same for the same seed
+#[cfg(test)]
+pub(crate) fn random_graph(node_quantity: usize, arc_quantity: usize, max_weight: usize, seed: u64) -> Graph {
+    let mut state = seed;
+    let mut next = |bound: usize| {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        (state % bound as u64) as usize
+    };
+    let mut arcs = Vec::with_capacity(arc_quantity);
+    for _ in 0..arc_quantity {
+        let from = next(node_quantity) + 1;
+        let to = next(node_quantity) + 1;
+        let cost = next(max_weight + 1);
+        arcs.push((from, to, cost));
     }
+    Graph::from_arcs(node_quantity, &arcs).unwrap()
 }
 
-
 #[test]
 fn graph_test() {
     let mut graph = Graph::new(6);
@@ -280,3 +291,63 @@ fn graph_test() {
     println!("{:?}", graph.radix_ss(1));
     println!("{:?}", graph.radix_p2p(1, 2));
 }
+
+#[test]
+fn path_test() {
+    let mut graph = Graph::new(6);
+    graph.add_edge(1, 2, 13);
+    graph.add_edge(1, 3, 0);
+    graph.add_edge(1, 4, 15);
+    graph.add_edge(1, 5, 20);
+    graph.add_edge(2, 4, 5);
+    graph.add_edge(3, 5, 9);
+    graph.add_edge(4, 6, 2);
+    graph.add_edge(5, 6, 4);
+    for algorithm in Algorithm::ALL {
+        let tree = graph.shortest_path_tree(algorithm, 1);
+        assert_eq!(tree.distances, vec![Some(0), Some(13), Some(0), Some(15), Some(9), Some(13)]);
+        assert_eq!(tree.path(6), Some(vec![1, 3, 5, 6]));
+        let (distance, path) = graph.path(algorithm, 1, 6).unwrap();
+        assert_eq!(graph.path_length(&path), Some(distance));
+        assert_eq!(graph.path(algorithm, 6, 1), None);
+    }
+    assert_eq!(graph.path(Algorithm::Classic, 2, 2), Some((0, vec![2])));
+}
+
+#[test]
+fn dial_radix_test() {
+    for seed in 1..30 {
+        let graph = random_graph(50, 200, 1000, seed);
+        for src in [1, 25, 50] {
+            assert_eq!(graph.dial_tree(src).distances, graph.djikstra_classic_ss(src));
+            assert_eq!(graph.radix_tree(src).distances, graph.djikstra_classic_ss(src));
+        }
+    }
+}
+
+#[test]
+fn from_arcs_test() {
+    let arcs = [(1, 2, 4), (1, 2, 4), (2, 3, 1), (1, 3, 7), (3, 1, 2)];
+    let loaded = Graph::from_arcs(3, &arcs).unwrap();
+    let mut built = Graph::new(3);
+    for (from, to, cost) in arcs {
+        built.add_edge(from, to, cost);
+    }
+    //parallel arcs of equal cost stay apart
+    assert_eq!(loaded.arc_quantity(), 5);
+    assert_eq!(built.arc_quantity(), 5);
+    assert_eq!(loaded.max_weight(), 7);
+    assert_eq!(loaded.min_weight(), 1);
+    assert_eq!(loaded.arcs(0).collect::<Vec<_>>(), vec![(1, 4), (1, 4), (2, 7)]);
+    assert_eq!(loaded.reversed().arcs(0).collect::<Vec<_>>(), vec![(2, 2)]);
+    for src in 1..=3 {
+        assert_eq!(loaded.djikstra_classic_ss(src), built.djikstra_classic_ss(src));
+    }
+    //the reverse arcs are built by the first backward search and kept up to date afterwards
+    assert_eq!(built.bidirectional_djikstra_p2p(1, 3).map(|meeting| meeting.distance), Some(5));
+    built.add_edge(1, 3, 0);
+    assert_eq!(built.bidirectional_djikstra_p2p(1, 3).map(|meeting| meeting.distance), Some(0));
+    assert!(matches!(Graph::from_arcs(2, &[(1, 2, 1 << 32)]), Err(DimacsError::TooLarge { what: "arc cost", .. })));
+    assert!(matches!(Graph::from_arcs(2, &[(1, 2, 1), (0, 1, 1)]), Err(DimacsError::ArcNodeOutOfRange { arc: 2, node: 0, .. })));
+    assert!(matches!(Graph::from_arcs(2, &[(1, 3, 1)]), Err(DimacsError::ArcNodeOutOfRange { arc: 1, node: 3, .. })));
+}
@@ -1,23 +1,85 @@
-use std::{io, fs};
+use std::fs;
 use std::fs::File;
-use std::io::{BufRead, Write};
-use std::path::Path;
-use std::thread;
+use std::io::{self, BufWriter, Write};
 use std::time::{Instant};
 
 use dialoguer::{console::Term, Input, theme::ColorfulTheme, Select};
 
 use lib::*;
+use lib::alt::*;
+use lib::apsp::*;
+use lib::astar::*;
+use lib::batch::*;
+use lib::bench::*;
+use lib::cache::*;
+use lib::ch::*;
+use lib::delta_stepping::*;
+use lib::dimacs::*;
+use lib::generators::*;
+use lib::queue::*;
+use lib::signed::*;
+use lib::stats::*;
+use lib::verify::*;
 
 
-fn read_lines<P>(filename: P) -> io::Result<io::Lines<io::BufReader<File>>>
-where P: AsRef<Path>, {
-    let file = File::open(filename)?;
-    Ok(io::BufReader::new(file).lines())
+const ALT_LANDMARKS: usize = 16;
+const GENERATED_SOURCES: usize = 10;
+const GENERATED_QUERIES: usize = 100;
+
+fn or_report<T>(path: &str, result: Result<T, DimacsError>) -> Option<T> {
+    result.map_err(|e| eprintln!("{path}: {e}")).ok()
+}
+
+//the hierarchy is kept next to the graph as <graph>.ch and rebuilt when that file is missing or older
+fn load_hierarchy(graph_file: &str, g: &Graph) -> Option<ContractionHierarchy> {
+    let hierarchy_file = format!("{graph_file}.ch");
+    if cache_is_fresh(graph_file, &hierarchy_file) {
+        return or_report(&hierarchy_file, ContractionHierarchy::read(&hierarchy_file));
+    }
+    let start = Instant::now();
+    let hierarchy = ContractionHierarchy::new(g);
+    println!("PREPROCESSING:{} shortcuts: {}", start.elapsed().as_secs_f64(), hierarchy.shortcut_quantity());
+    if let Err(e) = hierarchy.save(&hierarchy_file) {
+        eprintln!("{hierarchy_file}: {e}");
+    }
+    Some(hierarchy)
+}
+
+//parsed graphs are kept next to the .gr file as <graph>.bin and reloaded from there while it is newer
+fn load_cached_graph(graph_file: &str) -> Option<Graph> {
+    let cache_file = cache_path(graph_file);
+    if cache_is_fresh(graph_file, &cache_file) {
+        match Graph::open_cache(&cache_file) {
+            Ok(g) => return Some(g),
+            Err(e) => eprintln!("{cache_file}: {e}, reading {graph_file}"),
+        }
+    }
+    let g = or_report(graph_file, load_graph(graph_file))?;
+    if let Err(e) = g.save_cache(&cache_file) {
+        eprintln!("{cache_file}: {e}");
+    }
+    Some(g)
 }
 
+//lista_3 bench <inputs directory> <output csv> [repetitions] [warm-up queries] runs the Test mode without prompts
 fn main() {
-    let choices = ["Test", "USA", "Manual"];
+    let args: Vec<String> = std::env::args().collect();
+    if args.get(1).map(String::as_str) == Some("bench") {
+        let (Some(input), Some(output)) = (args.get(2), args.get(3)) else {
+            eprintln!("usage: {} bench <inputs directory> <output csv> [repetitions] [warm-up queries]", args[0]);
+            return;
+        };
+        let count = |at: usize, default: usize| args.get(at).map_or(Some(default), |count| count.parse().ok());
+        let (Some(repetitions), Some(warmup)) = (count(4, 5), count(5, 1)) else {
+            eprintln!("repetitions and warm-up queries are counts");
+            return;
+        };
+        if let Err(e) = benchmark(input, output, BenchOptions { repetitions, warmup }) {
+            eprintln!("{output}: {e}");
+        }
+        return;
+    }
+    let choices = ["Test", "USA", "Manual", "Negative arcs", "All pairs", "Generate", "Verify"];
     let selection = Select::with_theme(&ColorfulTheme::default())
         .with_prompt("SELECT MODE")
         .items(&choices)
@@ -32,6 +94,10 @@ fn main() {
         0 => test(),
         1 => usa(),
         2 => manual(),
+        3 => signed(),
+        4 => all_pairs(),
+        5 => generate(),
+        6 => verification(),
         _ => panic!("somthing went wrong"),
     }    
 
@@ -43,68 +109,103 @@ fn test() {
         .with_prompt("Provide inputs directory path")
         .interact_text()
         .unwrap();
-    let paths = fs::read_dir(input).unwrap();
-    let mut graphs: Vec<String> = Vec::new();
-    let mut challanges: Vec<String> = Vec::new();
-    //let paths = fs::read_dir(r"C:\\Users\\neuma\\Desktop\\TEST".to_string()).unwrap();
-    for path in paths {
-        let tmp = path.unwrap().path().display().to_string();
-        println!("{:?}", tmp);
-        if tmp.contains(".gr") {
-            graphs.push(tmp);
-        } else if tmp.contains(".ss") {
-            challanges.push(tmp);
-        }
+    let output : String = Input::with_theme(&ColorfulTheme::default())
+        .with_prompt("Provide output csv file")
+        .default("./data/dijkstra_data.csv".to_string())
+        .interact_text()
+        .unwrap();
+    let repetitions : usize = Input::with_theme(&ColorfulTheme::default())
+        .with_prompt("Provide repetitions")
+        .default(5)
+        .interact_text()
+        .unwrap();
+    let warmup : usize = Input::with_theme(&ColorfulTheme::default())
+        .with_prompt("Provide warm-up queries")
+        .default(1)
+        .interact_text()
+        .unwrap();
+    if let Err(e) = benchmark(&input, &output, BenchOptions { repetitions, warmup }) {
+        eprintln!("{output}: {e}");
     }
-    let mut file = File::create("./data/dijkstra_data.csv").unwrap();
-    for i in 0..graphs.len() {
-        file.write_all(b"algo;graph;n;e;time\n").unwrap();
-        println!("{}", graphs[i]);
-        let name = graphs[i].replace("C:\\Users\\neuma\\Desktop\\TEST\\", "");
-        let mut n: usize = 0;
-        let mut e: usize = 0;
-        let mut g = Graph::default();
-        if let Ok(lines) = read_lines(graphs[i].clone()) {
-            for line in lines.flatten() {
-                if line.starts_with('p') {
-                    n = line.split_whitespace().collect::<Vec<&str>>()[2].parse().unwrap();
-                    e = line.split_whitespace().collect::<Vec<&str>>()[3].parse().unwrap();
-                    g = Graph::new(n);
-                } else if line.starts_with('a') {
-                    let tmp = line.split_whitespace().collect::<Vec<&str>>();
-                    g.add_edge(tmp[1].parse().unwrap(), tmp[2].parse().unwrap(), tmp[3].parse().unwrap())
-                }
-            }
-        }
-        let mut challenge_file = "".to_string();
-        for challange in challanges.clone() {
-            if challange.contains(&graphs[i].clone().replace(".gr", "")) {
-                challenge_file = challange;
-                break;
+}
+
+//Every .gr file of the directory against its .ss and .p2p challenges (same name, other extension),
+//one CSV row per algorithm, graph and challenge
+fn benchmark(input: &str, output: &str, options: BenchOptions) -> io::Result<()> {
+    let mut graphs: Vec<String> = fs::read_dir(input)?
+        .filter_map(|path| path.ok())
+        .map(|path| path.path().display().to_string())
+        .filter(|path| path.ends_with(".gr"))
+        .collect();
+    graphs.sort();
+    let mut file = BufWriter::new(File::create(output)?);
+    writeln!(file, "{CSV_HEADER}")?;
+    for graph_file in &graphs {
+        println!("{graph_file}");
+        let Some(g) = load_cached_graph(graph_file) else {
+            continue;
+        };
+        let stem = graph_file.trim_end_matches(".gr");
+        let row = |algorithm: &str, challenge_file: &str, queries: usize, summary: Summary, counters: SearchStats| {
+            let mut measurement = Measurement {
+                algorithm: algorithm.to_string(),
+                graph: display_name(graph_file),
+                challenge: display_name(challenge_file),
+                node_quantity: g.node_quantity(),
+                arc_quantity: g.arc_quantity(),
+                queries,
+                repetitions: options.repetitions,
+                summary,
+                scanned_nodes: None,
+                relaxations: None,
+            };
+            measurement.set_counters(Some(counters));
+            measurement
+        };
+        let ss_file = format!("{stem}.ss");
+        if let Some(Challenge::Ss(ss)) = fs::metadata(&ss_file).ok().and_then(|_| or_report(&ss_file, read_challenge(&ss_file, g.node_quantity()))) {
+            //one row per priority queue behind the same Dijkstra loop, timed without counting
+            for kind in QueueKind::ALL {
+                let summary = measure(options, ss.len(), |query| {
+                    g.queue_tree(kind, ss[query], None);
+                });
+                let counters = count(ss.len(), |query| g.queue_tree_stats::<SearchStats>(kind, ss[query], None).1);
+                row(kind.name(), &ss_file, ss.len(), summary, counters).write_csv(&mut file)?;
             }
+            let delta_stepping = DeltaStepping::new(&g, g.default_delta());
+            let summary = measure(options, ss.len(), |query| {
+                delta_stepping.ss(ss[query]);
+            });
+            let counters = count(ss.len(), |query| delta_stepping.ss_stats::<SearchStats>(ss[query]).1);
+            row("delta-stepping", &ss_file, ss.len(), summary, counters).write_csv(&mut file)?;
         }
-        let mut ss: Vec<usize> = Vec::new();
-        if let Ok(lines) = read_lines(challenge_file) {
-            for line in lines.flatten() {
-                if line.starts_with('s') {
-                    let tmp = line.split_whitespace().collect::<Vec<&str>>();
-                    ss.push(tmp[1].parse().unwrap());
-                }
+        //goal directed search only pays off point to point, the landmarks are chosen once per graph
+        let p2p_file = format!("{stem}.p2p");
+        if let Some(Challenge::P2p(p2ps)) = fs::metadata(&p2p_file).ok().and_then(|_| or_report(&p2p_file, read_challenge(&p2p_file, g.node_quantity()))) {
+            for (name, kind) in [("classic_p2p", QueueKind::LazyBinaryHeap), ("dial_p2p", QueueKind::Dial), ("radix_p2p", QueueKind::Radix)] {
+                let summary = measure(options, p2ps.len(), |query| {
+                    let (s, t) = p2ps[query];
+                    g.queue_tree(kind, s, Some(t));
+                });
+                let counters = count(p2ps.len(), |query| {
+                    let (s, t) = p2ps[query];
+                    g.queue_tree_stats::<SearchStats>(kind, s, Some(t)).1
+                });
+                row(name, &p2p_file, p2ps.len(), summary, counters).write_csv(&mut file)?;
             }
-        }
-        for src in ss {
-            let mut start = Instant::now();
-            g.djikstra_classic_ss(src);
-            file.write_all(format!("classic;{};{};{};{}\n", name, n, e, start.elapsed().as_secs_f32()).as_bytes()).unwrap();
-            start = Instant::now();
-            g.dial_ss(src);
-            file.write_all(format!("dial;{};{};{};{}\n", name, n, e, start.elapsed().as_secs_f32()).as_bytes()).unwrap();
-            //g.radix_ss(src);
-            //println!("{:?}", g.djikstra_classic_ss(src));
-            //println!("{:?}", g.dial_ss(src));
-            //println!("{:?}", g.radix_ss(src));
+            let landmarks = Landmarks::new(&g, ALT_LANDMARKS, LandmarkSelection::Avoid);
+            let summary = measure(options, p2ps.len(), |query| {
+                let (s, t) = p2ps[query];
+                g.alt_p2p(&landmarks, s, t);
+            });
+            let counters = count(p2ps.len(), |query| {
+                let (s, t) = p2ps[query];
+                g.alt_p2p_stats::<SearchStats>(&landmarks, s, t).1
+            });
+            row("alt_p2p", &p2p_file, p2ps.len(), summary, counters).write_csv(&mut file)?;
         }
     }
+    file.flush()
 }
 
 fn manual() {
@@ -119,52 +220,214 @@ fn manual() {
         panic!("User did not select anything")
     }
 
+    let algorithm = Algorithm::ALL[algorithm_selection.unwrap()];
+
     let graph_input : String = Input::with_theme(&ColorfulTheme::default())
         .with_prompt("Provide graph file")
         .interact_text()
         .unwrap();
 
-    let mut g = Graph::default();
-    if let Ok(lines) = read_lines(graph_input) {
-        for line in lines.flatten() {
-            if line.starts_with('p') {
-                g = Graph::new(line.split_whitespace().collect::<Vec<&str>>()[2].parse().unwrap());
-            } else if line.starts_with('a') {
-                let tmp = line.split_whitespace().collect::<Vec<&str>>();
-                g.add_edge(tmp[1].parse().unwrap(), tmp[2].parse().unwrap(), tmp[3].parse().unwrap())
-            }
-        }
-    }
+    let Some(g) = or_report(&graph_input, load_graph(&graph_input)) else {
+        return;
+    };
 
     let challange_input : String = Input::with_theme(&ColorfulTheme::default())
         .with_prompt("Provide challange file")
         .interact_text()
         .unwrap();
 
-    let mut ss: Vec<usize> = Vec::new();
-    if let Ok(lines) = read_lines(challange_input) {
-        for line in lines.flatten() {
-            if line.starts_with('s') {
-                let tmp = line.split_whitespace().collect::<Vec<&str>>();
-                ss.push(tmp[1].parse().unwrap());
+    let Some(challenge) = or_report(&challange_input, read_challenge(&challange_input, g.node_quantity())) else {
+        return;
+    };
+
+    let res_input : String = Input::with_theme(&ColorfulTheme::default())
+        .with_prompt("Provide resoult file")
+        .interact_text()
+        .unwrap();
+
+    let header = ResultHeader::new(algorithm, &graph_input, &challange_input, &g);
+    let mut file = File::create(res_input).unwrap();
+    match challenge {
+        Challenge::Ss(ss) => {
+            let start = Instant::now();
+            for src in &ss {
+                g.shortest_path_tree(algorithm, *src);
             }
-        }
+            let average_time_ms = start.elapsed().as_secs_f64() * 1000.0 / ss.len().max(1) as f64;
+            write_ss_result(&mut file, &header, average_time_ms).unwrap();
+        },
+        Challenge::P2p(p2ps) => {
+            let start = Instant::now();
+            let queries: Vec<(usize, usize, Option<usize>)> = p2ps
+                .iter()
+                .map(|&(s, t)| (s, t, g.path(algorithm, s, t).map(|(distance, _)| distance)))
+                .collect();
+            let average_time_ms = start.elapsed().as_secs_f64() * 1000.0 / p2ps.len().max(1) as f64;
+            write_p2p_result(&mut file, &header, average_time_ms, &queries).unwrap();
+        },
+    }
+}
+
+//graphs with negative arc costs, prints the distances or a negative cycle reachable from the source
+fn signed() {
+    let alorithm_choices = vec!["bellman-ford", "spfa", "spfa small label first", "goldberg-radzik"];
+    let algorithm_selection = Select::with_theme(&ColorfulTheme::default())
+        .with_prompt("SELECT ALGORITHM")
+        .items(&alorithm_choices)
+        .default(0)
+        .interact_on_opt(&Term::stderr())
+        .expect("failed");
+    if algorithm_selection.is_none() {
+        panic!("User did not select anything")
     }
 
+    let graph_input : String = Input::with_theme(&ColorfulTheme::default())
+        .with_prompt("Provide graph file")
+        .interact_text()
+        .unwrap();
+
+    let Some(g) = or_report(&graph_input, read_signed_graph(&graph_input)) else {
+        return;
+    };
+    let g = g.to_signed_graph();
+
+    let src : usize = Input::with_theme(&ColorfulTheme::default())
+        .with_prompt("Provide source node")
+        .validate_with(|node: &usize| if (1..=g.node_quantity()).contains(node) { Ok(()) } else { Err("no such node") })
+        .interact_text()
+        .unwrap();
+
+    let start = Instant::now();
+    let result = match algorithm_selection.unwrap() {
+        0 => g.bellman_ford(src),
+        1 => g.spfa(src, SpfaOrder::Fifo),
+        2 => g.spfa(src, SpfaOrder::SmallLabelFirst),
+        _ => g.goldberg_radzik(src),
+    };
+    println!("TIME:{}", start.elapsed().as_secs_f64());
+    match result {
+        Ok(tree) => {
+            for (node, distance) in tree.distances.iter().enumerate() {
+                if let Some(distance) = distance {
+                    println!("{} {distance}", node + 1);
+                }
+            }
+        },
+        Err(cycle) => println!("NEGATIVE CYCLE:{:?} cost: {}", cycle.nodes, cycle.cost),
+    }
+}
+
+//full distance table written to a matrix file, Johnson streams it row by row
+fn all_pairs() {
+    let alorithm_choices = vec!["johnson", "floyd-warshall"];
+    let algorithm_selection = Select::with_theme(&ColorfulTheme::default())
+        .with_prompt("SELECT ALGORITHM")
+        .items(&alorithm_choices)
+        .default(0)
+        .interact_on_opt(&Term::stderr())
+        .expect("failed");
+    if algorithm_selection.is_none() {
+        panic!("User did not select anything")
+    }
+
+    let graph_input : String = Input::with_theme(&ColorfulTheme::default())
+        .with_prompt("Provide graph file")
+        .interact_text()
+        .unwrap();
+
+    let Some(g) = or_report(&graph_input, read_signed_graph(&graph_input)) else {
+        return;
+    };
+    let g = g.to_signed_graph();
+
     let res_input : String = Input::with_theme(&ColorfulTheme::default())
         .with_prompt("Provide resoult file")
         .interact_text()
         .unwrap();
 
-    for src in ss {
-        g.djikstra_classic_ss(src);
-        g.dial_ss(src);
-        //g.radix_ss(src);
-        //println!("{:?}", g.djikstra_classic_ss(src));
-        //println!("{:?}", g.dial_ss(src));
-        //println!("{:?}", g.radix_ss(src));
+    let start = Instant::now();
+    let mut file = BufWriter::new(File::create(res_input).unwrap());
+    let written = match algorithm_selection.unwrap() {
+        0 => Johnson::new(&g).map(|johnson| johnson.write(&mut file)),
+        _ => FloydWarshall::new(&g).map(|floyd_warshall| floyd_warshall.matrix().write(&mut file)).map_err(JohnsonError::from),
+    };
+    match written {
+        Ok(result) => {
+            result.and_then(|_| file.flush()).unwrap();
+            println!("TIME:{}", start.elapsed().as_secs_f64());
+        },
+        Err(JohnsonError::NegativeCycle(cycle)) => println!("NEGATIVE CYCLE:{:?} cost: {}", cycle.nodes, cycle.cost),
+        Err(JohnsonError::TooLarge(e)) => eprintln!("{graph_input}: {e}"),
+    }
+}
+
+//synthetic instances of the DIMACS challenge families, ready for the Test mode
+fn generate() {
+    let family_choices: Vec<&str> = Family::ALL.iter().map(|family| family.name()).collect();
+    let family_selection = Select::with_theme(&ColorfulTheme::default())
+        .with_prompt("SELECT FAMILY")
+        .items(&family_choices)
+        .default(0)
+        .interact_on_opt(&Term::stderr())
+        .expect("failed");
+    if family_selection.is_none() {
+        panic!("User did not select anything")
+    }
+    let family = Family::ALL[family_selection.unwrap()];
+
+    let e : u32 = Input::with_theme(&ColorfulTheme::default())
+        .with_prompt("Provide exponent (log2 n for -n families, log4 C for -C families)")
+        .interact_text()
+        .unwrap();
+
+    let seed : u64 = Input::with_theme(&ColorfulTheme::default())
+        .with_prompt("Provide seed")
+        .default(0)
+        .interact_text()
+        .unwrap();
+
+    let directory : String = Input::with_theme(&ColorfulTheme::default())
+        .with_prompt("Provide output directory path")
+        .interact_text()
+        .unwrap();
+
+    match generate_family(&directory, family, e, seed, GENERATED_SOURCES, GENERATED_QUERIES) {
+        Ok(paths) => {
+            for path in paths {
+                println!("{}", path.display());
+            }
+        },
+        Err(e) => eprintln!("{directory}: {e}"),
+    }
+}
+
+//every single source algorithm from the sources of a .ss file, checked against each other and their certificates
+fn verification() {
+    let graph_input : String = Input::with_theme(&ColorfulTheme::default())
+        .with_prompt("Provide graph file")
+        .interact_text()
+        .unwrap();
+
+    let Some(g) = load_cached_graph(&graph_input) else {
+        return;
+    };
+
+    let challange_input : String = Input::with_theme(&ColorfulTheme::default())
+        .with_prompt("Provide challange file")
+        .interact_text()
+        .unwrap();
+
+    let ss = match or_report(&challange_input, read_challenge(&challange_input, g.node_quantity())) {
+        Some(Challenge::Ss(ss)) => ss,
+        Some(Challenge::P2p(p2ps)) => p2ps.into_iter().map(|(s, _)| s).collect(),
+        None => return,
+    };
+
+    let algorithms = ss_algorithms();
+    match verify(&g, &algorithms, &ss) {
+        Ok(()) => println!("OK: {} algorithms agree from {} sources", algorithms.len(), ss.len()),
+        Err(mismatch) => println!("MISMATCH: {mismatch}"),
     }
-    
 }
 
 fn usa() {
@@ -197,7 +460,7 @@ fn usa() {
         panic!("User did not select anything")
     }
 
-    let alorithm_choices = vec!["basic", "dial's", "radix"];
+    let alorithm_choices = vec!["basic", "dial's", "radix", "A*", "bidirectional", "contraction hierarchies", "delta-stepping"];
     let algorithm_selection = Select::with_theme(&ColorfulTheme::default())
         .with_prompt("SELECT ALGORITHM")
         .items(&alorithm_choices)
@@ -210,13 +473,17 @@ fn usa() {
 
     let mut graph_file: String = "".to_string();
     let mut challenge_file: String = "".to_string();
+    let mut coordinates_file: String = "".to_string();
 
     for path in paths{
         let tmp = path.unwrap().path().display().to_string();
         if tmp.contains(graph_choices[graph_selection.unwrap()]){
-            if tmp.contains(".gr"){
+            if tmp.ends_with(".gr"){
                 graph_file = tmp.clone();
             }
+            if tmp.ends_with(".co"){
+                coordinates_file = tmp.clone();
+            }
             if tmp.contains(challenge_choices[challenge_selection.unwrap()]){
                 challenge_file = tmp.clone();
             }
@@ -224,72 +491,102 @@ fn usa() {
     }
     //println!("{:?}", graph_file);
     //println!("{:?}", challenge_file);
-    let mut g = Graph::default();
-    if let Ok(lines) = read_lines(graph_file) {
-        for line in lines.flatten() {
-            if line.starts_with('p') {
-                g = Graph::new(line.split_whitespace().collect::<Vec<&str>>()[2].parse().unwrap());
-            } else if line.starts_with('a') {
-                let tmp = line.split_whitespace().collect::<Vec<&str>>();
-                g.add_edge(tmp[1].parse().unwrap(), tmp[2].parse().unwrap(), tmp[3].parse().unwrap())
-            }
-        }
-    }
+    let Some(g) = load_cached_graph(&graph_file) else {
+        return;
+    };
+    let Some(challenge) = or_report(&challenge_file, read_challenge(&challenge_file, g.node_quantity())) else {
+        return;
+    };
 
-    let start: Instant = Instant::now();
-    //p2p if 0
-    if challenge_selection.unwrap() == 0 {
-        let mut p2ps: Vec<(usize, usize)> = Vec::new();
-        if let Ok(lines) = read_lines(challenge_file) {
-            for line in lines.flatten() {
-                if line.starts_with('q') {
-                    let tmp = line.split_whitespace().collect::<Vec<&str>>();
-                    p2ps.push((tmp[1].parse().unwrap(), tmp[2].parse().unwrap()));
-                }
-            }
-        }
-        for p2p in p2ps{
-            let gc = g.clone();
-            match algorithm_selection.unwrap() {
-                0 => {
-                    thread::spawn(move || {  
-                        println!("{:?}", gc.clone().djikstra_classic_p2p(p2p.0, p2p.1));
-                    });
-                },
-                1 => {
-                    println!("{:?}", g.dial_p2p(p2p.0, p2p.1))
-                },
-                2 => {
-                    println!("{:?}", g.radix_p2p(p2p.0, p2p.1))
-                },
-                _ => panic!("Something went wrong!")
-            }
+    //A* needs the .co file of the graph and only answers p2p queries
+    let heuristic = if algorithm_selection.unwrap() == 3 {
+        if let Challenge::Ss(_) = challenge {
+            eprintln!("A* only answers p2p challenges");
+            return;
         }
+        let Some(coordinates) = or_report(&coordinates_file, read_coordinates(&coordinates_file)) else {
+            return;
+        };
+        let Some(heuristic) = or_report(&coordinates_file, GeometricHeuristic::new(&g, &coordinates)) else {
+            return;
+        };
+        Some(heuristic)
     } else {
-        let mut ss: Vec<usize> = Vec::new();
-        if let Ok(lines) = read_lines(challenge_file) {
-            for line in lines.flatten() {
-                if line.starts_with('s') {
-                    let tmp = line.split_whitespace().collect::<Vec<&str>>();
-                    ss.push(tmp[1].parse().unwrap());
-                }
-            }
+        None
+    };
+
+    if (4..=5).contains(&algorithm_selection.unwrap()) {
+        if let Challenge::Ss(_) = challenge {
+            eprintln!("{} only answers p2p challenges", alorithm_choices[algorithm_selection.unwrap()]);
+            return;
         }
-        for src in ss{
-            match algorithm_selection.unwrap() {
-                0 => {
-                    println!("{:?}", g.djikstra_classic_ss(src))
+    }
+    let hierarchy = if algorithm_selection.unwrap() == 5 {
+        let Some(hierarchy) = load_hierarchy(&graph_file, &g) else {
+            return;
+        };
+        Some(hierarchy)
+    } else {
+        None
+    };
+
+    let delta_stepping = if algorithm_selection.unwrap() == 6 {
+        let delta : usize = Input::with_theme(&ColorfulTheme::default())
+            .with_prompt("Provide delta")
+            .default(g.default_delta())
+            .validate_with(|delta: &usize| if *delta > 0 { Ok(()) } else { Err("delta has to be positive") })
+            .interact_text()
+            .unwrap();
+        Some(DeltaStepping::new(&g, delta))
+    } else {
+        None
+    };
+
+    //queries share the graph on a bounded pool, answers are printed in the order of the challenge file
+    let runner = BatchRunner::new(0).unwrap();
+    let algorithm = algorithm_selection.unwrap();
+    let start: Instant = Instant::now();
+    let mut answers = match &challenge {
+        Challenge::P2p(p2ps) => {
+            runner.run(p2ps, |&(s, t)| match algorithm {
+                0 => format!("{:?}", g.djikstra_classic_p2p(s, t)),
+                1 => format!("{:?}", g.dial_p2p(s, t)),
+                2 => format!("{:?}", g.radix_p2p(s, t)),
+                3 => {
+                    let astar = g.astar_p2p(heuristic.as_ref().unwrap(), s, t);
+                    format!("{:?} settled: {}", astar.distance, astar.settled)
                 },
-                1 => {
-                    println!("{:?}", g.dial_ss(src))
+                4 => match g.bidirectional_djikstra_p2p(s, t) {
+                    Some(meeting) => format!("Some({}) met at {}", meeting.distance, meeting.node),
+                    None => "None".to_string(),
                 },
-                2 => {
-                    println!("{:?}", g.radix_ss(src))
-                   // g.radix_ss(src);
+                5 => match hierarchy.as_ref().unwrap().path(s, t) {
+                    Some((distance, path)) => format!("Some({}) over {} nodes", distance, path.len()),
+                    None => "None".to_string(),
                 },
-                _ => panic!("Something went wrong!")
-            }
+                6 => format!("{:?}", delta_stepping.as_ref().unwrap().ss(s)[t - 1]),
+                _ => panic!("Something went wrong!"),
+            })
+        },
+        Challenge::Ss(ss) => {
+            runner.run(ss, |&src| match algorithm {
+                0 => format!("{:?}", g.djikstra_classic_ss(src)),
+                1 => format!("{:?}", g.dial_ss(src)),
+                2 => format!("{:?}", g.radix_ss(src)),
+                6 => format!("{:?}", delta_stepping.as_ref().unwrap().ss(src)),
+                _ => panic!("Something went wrong!"),
+            })
+        },
+    };
+    let elapsed = start.elapsed();
+    //the Dijkstra runs A* is compared with happen after the clock stopped, so every TIME is A* alone
+    if let (3, Challenge::P2p(p2ps)) = (algorithm, &challenge) {
+        for (answer, &(s, t)) in answers.iter_mut().zip(p2ps) {
+            answer.result += &format!(" (dijkstra: {})", g.djikstra_classic_p2p_settled(s, t).settled);
         }
     }
-    println!("TIME:{}", start.elapsed().as_secs_f64())
-}
\ No newline at end of file
+    for answer in &answers {
+        println!("{} TIME:{}", answer.result, answer.time.as_secs_f64());
+    }
+    println!("TIME:{} THREADS:{}", elapsed.as_secs_f64(), runner.threads())
+}
@@ -0,0 +1,807 @@
+use std::collections::BinaryHeap;
+
+use crate::radix_heap::RadixHeap;
+use crate::stats::{self, Event, NoStats, Stats};
+use crate::{Graph, SearchNode, ShortestPathTree};
+
+//no node / not queued in the index based queues below
+const NIL: usize = usize::MAX;
+
+//Priority queue over nodes 0..node_quantity for Dijkstra: a key is never below the last extracted one and
+//at most max_step above it. A node is inserted once, decrease_key is only called on queued nodes
+pub trait MonotoneQueue {
+    fn with_bounds(max_step: usize, node_quantity: usize) -> Self where Self: Sized;
+    fn insert(&mut self, node: usize, key: usize);
+    fn decrease_key(&mut self, node: usize, key: usize);
+    //(node, key) with the smallest key, bucket queues record the buckets they look at
+    fn extract_min_with<S: Stats>(&mut self, stats: &mut S) -> Option<(usize, usize)>;
+
+    fn extract_min(&mut self) -> Option<(usize, usize)> where Self: Sized {
+        self.extract_min_with(&mut NoStats)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum QueueKind {
+    LazyBinaryHeap,
+    DaryHeap,
+    PairingHeap,
+    FibonacciHeap,
+    Dial,
+    Radix,
+    MultiLevelBuckets,
+}
+
+impl QueueKind {
+    pub const ALL: [QueueKind; 7] = [
+        QueueKind::LazyBinaryHeap,
+        QueueKind::DaryHeap,
+        QueueKind::PairingHeap,
+        QueueKind::FibonacciHeap,
+        QueueKind::Dial,
+        QueueKind::Radix,
+        QueueKind::MultiLevelBuckets,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            QueueKind::LazyBinaryHeap => "classic",
+            QueueKind::DaryHeap => "4-ary",
+            QueueKind::PairingHeap => "pairing",
+            QueueKind::FibonacciHeap => "fibonacci",
+            QueueKind::Dial => "dial",
+            QueueKind::Radix => "radix",
+            QueueKind::MultiLevelBuckets => "multilevel",
+        }
+    }
+}
+
+impl Graph {
+    //Dijkstra around any monotone queue, with a goal it stops once the goal is extracted
+    pub fn dijkstra<Q: MonotoneQueue>(&self, src: usize, goal: Option<usize>) -> ShortestPathTree {
+        self.dijkstra_with::<Q, _>(src, goal, &mut NoStats)
+    }
+
+    pub fn dijkstra_stats<Q: MonotoneQueue, S: Stats>(&self, src: usize, goal: Option<usize>) -> (ShortestPathTree, S) {
+        stats::collect(|stats| self.dijkstra_with::<Q, S>(src, goal, stats))
+    }
+
+    fn dijkstra_with<Q: MonotoneQueue, S: Stats>(&self, src: usize, goal: Option<usize>, stats: &mut S) -> ShortestPathTree {
+        let mut tree = ShortestPathTree::new(src, self.node_quantity);
+        let goal = goal.map(|goal| goal - 1);
+        let mut queue = Q::with_bounds(self.max_weight, self.node_quantity);
+        queue.insert(src - 1, 0);
+        stats.record(Event::Push);
+        while let Some((u, du)) = queue.extract_min_with(stats) {
+            stats.record(Event::Pop);
+            if Some(u) == goal {
+                break;
+            }
+            stats.record(Event::ScannedNode);
+            for (v, weight) in self.arcs(u) {
+                stats.record(Event::ScannedArc);
+                let alt_dist = du + weight;
+                match tree.distances[v] {
+                    Some(dv) if alt_dist >= dv => continue,
+                    //a labelled node with a larger distance is still queued
+                    Some(_) => {
+                        queue.decrease_key(v, alt_dist);
+                        stats.record(Event::DecreaseKey);
+                    },
+                    None => {
+                        queue.insert(v, alt_dist);
+                        stats.record(Event::Push);
+                    },
+                }
+                stats.record(Event::Relaxation);
+                tree.distances[v] = Some(alt_dist);
+                tree.predecessors[v] = Some(u + 1);
+            }
+        }
+        tree
+    }
+
+    pub fn queue_tree(&self, kind: QueueKind, src: usize, goal: Option<usize>) -> ShortestPathTree {
+        self.queue_tree_stats::<NoStats>(kind, src, goal).0
+    }
+
+    pub fn queue_tree_stats<S: Stats>(&self, kind: QueueKind, src: usize, goal: Option<usize>) -> (ShortestPathTree, S) {
+        match kind {
+            QueueKind::LazyBinaryHeap => self.dijkstra_stats::<LazyBinaryHeap, S>(src, goal),
+            QueueKind::DaryHeap => self.dijkstra_stats::<DaryHeap<4>, S>(src, goal),
+            QueueKind::PairingHeap => self.dijkstra_stats::<PairingHeap, S>(src, goal),
+            QueueKind::FibonacciHeap => self.dijkstra_stats::<FibonacciHeap, S>(src, goal),
+            QueueKind::Dial => self.dijkstra_stats::<DialBuckets, S>(src, goal),
+            QueueKind::Radix => self.dijkstra_stats::<RadixHeap, S>(src, goal),
+            QueueKind::MultiLevelBuckets => self.dijkstra_stats::<MultiLevelBuckets, S>(src, goal),
+        }
+    }
+}
+
+//std BinaryHeap, decrease_key pushes another entry and the outdated ones are skipped on extraction
+#[derive(Clone, Debug)]
+pub struct LazyBinaryHeap {
+    heap: BinaryHeap<SearchNode>,
+    //current key of every queued node
+    keys: Vec<usize>,
+}
+
+impl MonotoneQueue for LazyBinaryHeap {
+    fn with_bounds(_max_step: usize, node_quantity: usize) -> Self {
+        LazyBinaryHeap { heap: BinaryHeap::new(), keys: vec![NIL; node_quantity] }
+    }
+
+    fn insert(&mut self, node: usize, key: usize) {
+        self.keys[node] = key;
+        self.heap.push(SearchNode { id: node, distance: key });
+    }
+
+    fn decrease_key(&mut self, node: usize, key: usize) {
+        self.insert(node, key);
+    }
+
+    fn extract_min_with<S: Stats>(&mut self, _stats: &mut S) -> Option<(usize, usize)> {
+        while let Some(SearchNode { id, distance }) = self.heap.pop() {
+            if self.keys[id] == distance {
+                self.keys[id] = NIL;
+                return Some((id, distance));
+            }
+        }
+        None
+    }
+}
+
+//array heap with D children per node and the position of every node, so decrease_key sifts it up in place
+#[derive(Clone, Debug)]
+pub struct DaryHeap<const D: usize> {
+    heap: Vec<usize>,
+    positions: Vec<usize>,
+    keys: Vec<usize>,
+}
+
+impl<const D: usize> DaryHeap<D> {
+    fn place(&mut self, index: usize, node: usize) {
+        self.heap[index] = node;
+        self.positions[node] = index;
+    }
+
+    fn sift_up(&mut self, mut index: usize) {
+        let node = self.heap[index];
+        while index > 0 {
+            let parent = (index - 1) / D;
+            if self.keys[self.heap[parent]] <= self.keys[node] {
+                break;
+            }
+            self.place(index, self.heap[parent]);
+            index = parent;
+        }
+        self.place(index, node);
+    }
+
+    fn sift_down(&mut self, mut index: usize) {
+        let node = self.heap[index];
+        loop {
+            let first = index * D + 1;
+            let last = (first + D).min(self.heap.len());
+            let Some(child) = (first..last).min_by_key(|child| self.keys[self.heap[*child]]) else {
+                break;
+            };
+            if self.keys[self.heap[child]] >= self.keys[node] {
+                break;
+            }
+            self.place(index, self.heap[child]);
+            index = child;
+        }
+        self.place(index, node);
+    }
+}
+
+impl<const D: usize> MonotoneQueue for DaryHeap<D> {
+    fn with_bounds(_max_step: usize, node_quantity: usize) -> Self {
+        DaryHeap { heap: Vec::new(), positions: vec![NIL; node_quantity], keys: vec![0; node_quantity] }
+    }
+
+    fn insert(&mut self, node: usize, key: usize) {
+        self.keys[node] = key;
+        self.heap.push(node);
+        self.sift_up(self.heap.len() - 1);
+    }
+
+    fn decrease_key(&mut self, node: usize, key: usize) {
+        self.keys[node] = key;
+        self.sift_up(self.positions[node]);
+    }
+
+    fn extract_min_with<S: Stats>(&mut self, _stats: &mut S) -> Option<(usize, usize)> {
+        let last = self.heap.pop()?;
+        let min = if self.heap.is_empty() {
+            last
+        } else {
+            let min = self.heap[0];
+            self.place(0, last);
+            self.sift_down(0);
+            min
+        };
+        self.positions[min] = NIL;
+        Some((min, self.keys[min]))
+    }
+}
+
+//Heap ordered multiway tree kept as first child / next sibling links. decrease_key cuts the subtree and
+//links it with the root, extract_min merges the children of the root in two passes
+#[derive(Clone, Debug)]
+pub struct PairingHeap {
+    root: usize,
+    keys: Vec<usize>,
+    child: Vec<usize>,
+    sibling: Vec<usize>,
+    //previous sibling, or the parent for a first child
+    previous: Vec<usize>,
+}
+
+impl PairingHeap {
+    //root of the two trees combined
+    fn link(&mut self, a: usize, b: usize) -> usize {
+        if a == NIL {
+            return b;
+        }
+        if b == NIL {
+            return a;
+        }
+        let (parent, child) = if self.keys[b] < self.keys[a] { (b, a) } else { (a, b) };
+        self.sibling[child] = self.child[parent];
+        if self.child[parent] != NIL {
+            self.previous[self.child[parent]] = child;
+        }
+        self.previous[child] = parent;
+        self.child[parent] = child;
+        parent
+    }
+}
+
+impl MonotoneQueue for PairingHeap {
+    fn with_bounds(_max_step: usize, node_quantity: usize) -> Self {
+        PairingHeap {
+            root: NIL,
+            keys: vec![0; node_quantity],
+            child: vec![NIL; node_quantity],
+            sibling: vec![NIL; node_quantity],
+            previous: vec![NIL; node_quantity],
+        }
+    }
+
+    fn insert(&mut self, node: usize, key: usize) {
+        self.keys[node] = key;
+        self.child[node] = NIL;
+        self.sibling[node] = NIL;
+        self.previous[node] = NIL;
+        self.root = self.link(self.root, node);
+    }
+
+    fn decrease_key(&mut self, node: usize, key: usize) {
+        self.keys[node] = key;
+        if node == self.root {
+            return;
+        }
+        let previous = self.previous[node];
+        if self.child[previous] == node {
+            self.child[previous] = self.sibling[node];
+        } else {
+            self.sibling[previous] = self.sibling[node];
+        }
+        if self.sibling[node] != NIL {
+            self.previous[self.sibling[node]] = previous;
+        }
+        self.sibling[node] = NIL;
+        self.previous[node] = NIL;
+        self.root = self.link(self.root, node);
+    }
+
+    fn extract_min_with<S: Stats>(&mut self, _stats: &mut S) -> Option<(usize, usize)> {
+        if self.root == NIL {
+            return None;
+        }
+        let min = self.root;
+        let mut children = Vec::new();
+        let mut child = self.child[min];
+        while child != NIL {
+            let next = self.sibling[child];
+            self.sibling[child] = NIL;
+            self.previous[child] = NIL;
+            children.push(child);
+            child = next;
+        }
+        //pairs from the left, then everything into the last pair from the right
+        let pairs: Vec<usize> = children.chunks(2).map(|pair| self.link(pair[0], *pair.get(1).unwrap_or(&NIL))).collect();
+        self.root = pairs.into_iter().rev().fold(NIL, |root, tree| self.link(tree, root));
+        self.child[min] = NIL;
+        Some((min, self.keys[min]))
+    }
+}
+
+//Roots and siblings in circular doubly linked lists. decrease_key cuts a node that got smaller than its
+//parent and cascades through marked ancestors, extract_min links roots of equal degree
+#[derive(Clone, Debug)]
+pub struct FibonacciHeap {
+    min: usize,
+    keys: Vec<usize>,
+    parent: Vec<usize>,
+    child: Vec<usize>,
+    left: Vec<usize>,
+    right: Vec<usize>,
+    degree: Vec<usize>,
+    marked: Vec<bool>,
+    //root of every degree while consolidating
+    roots_by_degree: Vec<usize>,
+}
+
+impl FibonacciHeap {
+    //node becomes a one element list
+    fn isolate(&mut self, node: usize) {
+        self.left[node] = node;
+        self.right[node] = node;
+    }
+
+    //puts node into the list next to anchor
+    fn splice(&mut self, anchor: usize, node: usize) {
+        let right = self.right[anchor];
+        self.left[node] = anchor;
+        self.right[node] = right;
+        self.left[right] = node;
+        self.right[anchor] = node;
+    }
+
+    fn unlink(&mut self, node: usize) {
+        let (left, right) = (self.left[node], self.right[node]);
+        self.right[left] = right;
+        self.left[right] = left;
+        self.isolate(node);
+    }
+
+    fn add_root(&mut self, node: usize) {
+        self.parent[node] = NIL;
+        self.marked[node] = false;
+        if self.min == NIL {
+            self.isolate(node);
+            self.min = node;
+        } else {
+            self.splice(self.min, node);
+            if self.keys[node] < self.keys[self.min] {
+                self.min = node;
+            }
+        }
+    }
+
+    //moves node from the children of its parent to the roots
+    fn cut(&mut self, node: usize) {
+        let parent = self.parent[node];
+        if self.child[parent] == node {
+            self.child[parent] = if self.right[node] == node { NIL } else { self.right[node] };
+        }
+        self.unlink(node);
+        self.degree[parent] -= 1;
+        self.add_root(node);
+    }
+
+    //child stops being a root and goes under parent
+    fn adopt(&mut self, parent: usize, child: usize) {
+        self.unlink(child);
+        if self.child[parent] == NIL {
+            self.child[parent] = child;
+        } else {
+            self.splice(self.child[parent], child);
+        }
+        self.parent[child] = parent;
+        self.marked[child] = false;
+        self.degree[parent] += 1;
+    }
+
+    fn consolidate(&mut self, start: usize) {
+        let mut roots = vec![start];
+        let mut root = self.right[start];
+        while root != start {
+            roots.push(root);
+            root = self.right[root];
+        }
+        for mut root in roots {
+            let mut degree = self.degree[root];
+            while self.roots_by_degree[degree] != NIL {
+                let mut other = self.roots_by_degree[degree];
+                if self.keys[other] < self.keys[root] {
+                    std::mem::swap(&mut root, &mut other);
+                }
+                self.adopt(root, other);
+                self.roots_by_degree[degree] = NIL;
+                degree += 1;
+            }
+            self.roots_by_degree[degree] = root;
+        }
+        self.min = NIL;
+        for degree in 0..self.roots_by_degree.len() {
+            let root = std::mem::replace(&mut self.roots_by_degree[degree], NIL);
+            if root != NIL && (self.min == NIL || self.keys[root] < self.keys[self.min]) {
+                self.min = root;
+            }
+        }
+    }
+}
+
+impl MonotoneQueue for FibonacciHeap {
+    fn with_bounds(_max_step: usize, node_quantity: usize) -> Self {
+        FibonacciHeap {
+            min: NIL,
+            keys: vec![0; node_quantity],
+            parent: vec![NIL; node_quantity],
+            child: vec![NIL; node_quantity],
+            left: vec![NIL; node_quantity],
+            right: vec![NIL; node_quantity],
+            degree: vec![0; node_quantity],
+            marked: vec![false; node_quantity],
+            //degrees stay below log_phi(node_quantity)
+            roots_by_degree: vec![NIL; 2 * usize::BITS as usize],
+        }
+    }
+
+    fn insert(&mut self, node: usize, key: usize) {
+        self.keys[node] = key;
+        self.child[node] = NIL;
+        self.degree[node] = 0;
+        self.add_root(node);
+    }
+
+    fn decrease_key(&mut self, node: usize, key: usize) {
+        self.keys[node] = key;
+        let mut parent = self.parent[node];
+        if parent != NIL && key < self.keys[parent] {
+            self.cut(node);
+            //cascading cut, a node that already lost a child leaves its parent too
+            while self.parent[parent] != NIL {
+                if !self.marked[parent] {
+                    self.marked[parent] = true;
+                    break;
+                }
+                let grandparent = self.parent[parent];
+                self.cut(parent);
+                parent = grandparent;
+            }
+        }
+        if key < self.keys[self.min] {
+            self.min = node;
+        }
+    }
+
+    fn extract_min_with<S: Stats>(&mut self, _stats: &mut S) -> Option<(usize, usize)> {
+        let min = self.min;
+        if min == NIL {
+            return None;
+        }
+        while self.child[min] != NIL {
+            self.cut(self.child[min]);
+        }
+        let next = self.right[min];
+        self.unlink(min);
+        self.min = NIL;
+        if next != min {
+            self.consolidate(next);
+        }
+        Some((min, self.keys[min]))
+    }
+}
+
+//Circular array of max_step + 1 buckets. Every queued key lies in [d, d + max_step] for the last
+//extracted d, so keys sharing a slot are always equal. Each bucket is a doubly linked list threaded
+//through next/previous, indexed by node, so a node is unlinked in O(1) when its key drops
+#[derive(Clone, Debug)]
+pub struct DialBuckets {
+    heads: Vec<usize>,
+    next: Vec<usize>,
+    previous: Vec<usize>,
+    keys: Vec<usize>,
+    current: usize,
+    queued: usize,
+}
+
+impl DialBuckets {
+    fn unlink(&mut self, node: usize) {
+        let bucket = self.keys[node] % self.heads.len();
+        if self.previous[node] == NIL {
+            self.heads[bucket] = self.next[node];
+        } else {
+            self.next[self.previous[node]] = self.next[node];
+        }
+        if self.next[node] != NIL {
+            self.previous[self.next[node]] = self.previous[node];
+        }
+        self.queued -= 1;
+    }
+}
+
+impl MonotoneQueue for DialBuckets {
+    fn with_bounds(max_step: usize, node_quantity: usize) -> Self {
+        DialBuckets {
+            heads: vec![NIL; max_step + 1],
+            next: vec![NIL; node_quantity],
+            previous: vec![NIL; node_quantity],
+            keys: vec![0; node_quantity],
+            current: 0,
+            queued: 0,
+        }
+    }
+
+    fn insert(&mut self, node: usize, key: usize) {
+        let bucket = key % self.heads.len();
+        self.keys[node] = key;
+        self.next[node] = self.heads[bucket];
+        self.previous[node] = NIL;
+        if self.heads[bucket] != NIL {
+            self.previous[self.heads[bucket]] = node;
+        }
+        self.heads[bucket] = node;
+        self.queued += 1;
+    }
+
+    fn decrease_key(&mut self, node: usize, key: usize) {
+        self.unlink(node);
+        self.insert(node, key);
+    }
+
+    //advances to the next non empty bucket and takes a node out of it
+    fn extract_min_with<S: Stats>(&mut self, stats: &mut S) -> Option<(usize, usize)> {
+        if self.queued == 0 {
+            return None;
+        }
+        stats.record(Event::BucketScan);
+        while self.heads[self.current % self.heads.len()] == NIL {
+            self.current += 1;
+            stats.record(Event::BucketScan);
+        }
+        let node = self.heads[self.current % self.heads.len()];
+        self.unlink(node);
+        Some((node, self.current))
+    }
+}
+
+impl MonotoneQueue for RadixHeap {
+    fn with_bounds(max_step: usize, node_quantity: usize) -> Self {
+        RadixHeap::new(max_step, node_quantity)
+    }
+
+    fn insert(&mut self, node: usize, key: usize) {
+        self.push(node, key);
+    }
+
+    fn decrease_key(&mut self, node: usize, key: usize) {
+        RadixHeap::decrease_key(self, node, key);
+    }
+
+    fn extract_min_with<S: Stats>(&mut self, stats: &mut S) -> Option<(usize, usize)> {
+        self.pop_with(stats)
+    }
+}
+
+//Keys written in base 2^bits with about two digits for max_step. A key sits on the level of the highest
+//digit where it differs from the last extracted key, in the bucket of its digit there. The lowest
+//non empty level holds the minimum; when that is not level 0 its first bucket is spread over the levels below
+#[derive(Clone, Debug)]
+pub struct MultiLevelBuckets {
+    bits: u32,
+    //level * base + digit
+    buckets: Vec<Vec<usize>>,
+    level_sizes: Vec<usize>,
+    keys: Vec<usize>,
+    //bucket and index in it of every queued node
+    slots: Vec<usize>,
+    indices: Vec<usize>,
+    last: usize,
+    len: usize,
+}
+
+impl MultiLevelBuckets {
+    fn base(&self) -> usize {
+        1 << self.bits
+    }
+
+    fn level(&self, key: usize) -> usize {
+        let difference = key ^ self.last;
+        if difference == 0 {
+            return 0;
+        }
+        ((usize::BITS - 1 - difference.leading_zeros()) / self.bits) as usize
+    }
+
+    fn digit(&self, key: usize, level: usize) -> usize {
+        (key >> (level as u32 * self.bits)) & (self.base() - 1)
+    }
+
+    fn place(&mut self, node: usize) {
+        let level = self.level(self.keys[node]);
+        let slot = level * self.base() + self.digit(self.keys[node], level);
+        self.slots[node] = slot;
+        self.indices[node] = self.buckets[slot].len();
+        self.buckets[slot].push(node);
+        self.level_sizes[level] += 1;
+    }
+
+    fn unlink(&mut self, node: usize) {
+        let (slot, index) = (self.slots[node], self.indices[node]);
+        self.buckets[slot].swap_remove(index);
+        if let Some(moved) = self.buckets[slot].get(index) {
+            self.indices[*moved] = index;
+        }
+        let level = slot / self.base();
+        self.level_sizes[level] -= 1;
+    }
+}
+
+impl MonotoneQueue for MultiLevelBuckets {
+    fn with_bounds(max_step: usize, node_quantity: usize) -> Self {
+        let step_bits = usize::BITS - max_step.leading_zeros();
+        let bits = step_bits.div_ceil(2).max(1);
+        let levels = usize::BITS.div_ceil(bits) as usize;
+        MultiLevelBuckets {
+            bits,
+            buckets: vec![Vec::new(); levels << bits],
+            level_sizes: vec![0; levels],
+            keys: vec![0; node_quantity],
+            slots: vec![NIL; node_quantity],
+            indices: vec![NIL; node_quantity],
+            last: 0,
+            len: 0,
+        }
+    }
+
+    fn insert(&mut self, node: usize, key: usize) {
+        self.keys[node] = key;
+        self.len += 1;
+        self.place(node);
+    }
+
+    fn decrease_key(&mut self, node: usize, key: usize) {
+        self.unlink(node);
+        self.keys[node] = key;
+        self.place(node);
+    }
+
+    fn extract_min_with<S: Stats>(&mut self, stats: &mut S) -> Option<(usize, usize)> {
+        if self.len == 0 {
+            return None;
+        }
+        let level = self.level_sizes.iter().position(|size| *size > 0).unwrap();
+        //digits below the one of the last key are empty on every level
+        let first = level * self.base() + self.digit(self.last, level);
+        let slot = (first..(level + 1) * self.base()).find(|slot| !self.buckets[*slot].is_empty()).unwrap();
+        stats.record_many(Event::BucketScan, slot - first + 1);
+        let node = if level == 0 {
+            self.buckets[slot][self.buckets[slot].len() - 1]
+        } else {
+            let bucket = std::mem::take(&mut self.buckets[slot]);
+            self.level_sizes[level] -= bucket.len();
+            let node = *bucket.iter().min_by_key(|node| self.keys[**node]).unwrap();
+            self.last = self.keys[node];
+            for moved in bucket {
+                self.place(moved);
+            }
+            node
+        };
+        self.unlink(node);
+        self.last = self.keys[node];
+        self.len -= 1;
+        Some((node, self.keys[node]))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cmp::Reverse;
+
+    use crate::queue::*;
+    use crate::random_graph;
+    use crate::stats::SearchStats;
+
+    //random monotone use like Dijkstra makes it, checked against a binary heap of (key, node)
+    fn check_queue<Q: MonotoneQueue>(max_step: usize) {
+        let mut state: u64 = 987654321;
+        let mut next = |bound: usize| {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            (state % bound as u64) as usize
+        };
+        let node_quantity = 200;
+        let mut queue = Q::with_bounds(max_step, node_quantity);
+        let mut keys: Vec<Option<usize>> = vec![None; node_quantity];
+        let mut extracted = vec![false; node_quantity];
+        let mut last = 0;
+        for _ in 0..10000 {
+            let node = next(node_quantity);
+            if next(3) > 0 {
+                if extracted[node] {
+                    continue;
+                }
+                let key = last + next(max_step + 1);
+                match keys[node] {
+                    None => queue.insert(node, key),
+                    Some(old) if key < old => queue.decrease_key(node, key),
+                    Some(_) => continue,
+                }
+                keys[node] = Some(key);
+            } else {
+                let expected = keys.iter().enumerate().filter_map(|(node, key)| key.map(|key| Reverse((key, node)))).max();
+                let extracted_pair = queue.extract_min();
+                match expected {
+                    None => assert_eq!(extracted_pair, None),
+                    Some(Reverse((key, _))) => {
+                        let (node, found) = extracted_pair.unwrap();
+                        assert_eq!(found, key);
+                        assert_eq!(keys[node], Some(key));
+                        keys[node] = None;
+                        extracted[node] = true;
+                        last = key;
+                    },
+                }
+            }
+        }
+        while let Some((node, key)) = queue.extract_min() {
+            assert!(key >= last);
+            assert_eq!(keys[node].take(), Some(key));
+            last = key;
+        }
+        assert!(keys.iter().all(|key| key.is_none()));
+    }
+
+    #[test]
+    fn dial_buckets_test() {
+        let mut buckets = DialBuckets::with_bounds(3, 5);
+        buckets.current = 2;
+        buckets.insert(0, 2);
+        buckets.insert(1, 5);
+        buckets.insert(2, 2);
+        buckets.insert(3, 3);
+        buckets.decrease_key(1, 4);
+        buckets.decrease_key(2, 2);
+        let mut extracted = Vec::new();
+        while let Some(pair) = buckets.extract_min() {
+            extracted.push(pair);
+        }
+        assert_eq!(extracted, vec![(2, 2), (0, 2), (3, 3), (1, 4)]);
+    }
+
+    #[test]
+    fn queues_test() {
+        for max_step in [0, 1, 7, 100, 5000] {
+            check_queue::<LazyBinaryHeap>(max_step);
+            check_queue::<DaryHeap<2>>(max_step);
+            check_queue::<DaryHeap<4>>(max_step);
+            check_queue::<PairingHeap>(max_step);
+            check_queue::<FibonacciHeap>(max_step);
+            check_queue::<DialBuckets>(max_step);
+            check_queue::<RadixHeap>(max_step);
+            check_queue::<MultiLevelBuckets>(max_step);
+        }
+    }
+
+    #[test]
+    fn dijkstra_queues_test() {
+        for seed in 1..20 {
+            let graph = random_graph(80, 300, 50, seed);
+            for src in [1, 40, 80] {
+                let expected = graph.djikstra_classic_ss(src);
+                for kind in QueueKind::ALL {
+                    assert_eq!(graph.queue_tree(kind, src, None).distances, expected, "{}", kind.name());
+                    assert_eq!(graph.queue_tree(kind, src, Some(17)).distance(17), expected[16], "{}", kind.name());
+                    //every reached node is scanned once, whatever the order
+                    let (_, stats) = graph.queue_tree_stats::<SearchStats>(kind, src, None);
+                    let reached: Vec<usize> = (0..80).filter(|node| expected[*node].is_some()).collect();
+                    assert_eq!(stats.scanned_nodes, reached.len());
+                    assert_eq!(stats.scanned_arcs, reached.iter().map(|node| graph.arcs(*node).count()).sum::<usize>());
+                    assert!(stats.relaxations >= reached.len() - 1);
+                    //every reached node is pushed once and popped once, any other relaxation is a decrease_key
+                    assert_eq!((stats.pushes, stats.pops), (reached.len(), reached.len()));
+                    assert_eq!(stats.relaxations, stats.pushes - 1 + stats.decrease_keys);
+                    let bucket_queue = matches!(kind, QueueKind::Dial | QueueKind::Radix | QueueKind::MultiLevelBuckets);
+                    assert_eq!(stats.bucket_scans >= stats.pops, bucket_queue, "{}", kind.name());
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,204 @@
+use crate::stats::{Event, NoStats, Stats};
+
+//Monotone priority queue over nodes 0..node_quantity for keys that never drop below the last popped key
+//and stay within max_step of it, as in Dijkstra with arc costs up to max_step (Ahuja, Mehlhorn, Orlin, Tarjan).
+//Bucket 0 holds keys equal to the last popped one and bucket i > 0 spans at most 2^(i - 1) keys, with
+//log2(max_step) + 2 buckets in total. A key only moves to lower buckets, which makes every operation
+//amortised O(log C)
+#[derive(Clone, Debug)]
+pub struct RadixHeap {
+    //largest key of every bucket, the last one is unbounded
+    upper: Vec<usize>,
+    buckets: Vec<Vec<usize>>,
+    keys: Vec<usize>,
+    //bucket and index in it of every queued node
+    positions: Vec<Option<(usize, usize)>>,
+    last: usize,
+    len: usize,
+}
+
+impl RadixHeap {
+    pub fn new(max_step: usize, node_quantity: usize) -> Self {
+        let bits = (usize::BITS - max_step.leading_zeros()) as usize;
+        let bucket_quantity = bits + 2;
+        let mut upper: Vec<usize> = (0..bucket_quantity).map(span).collect();
+        upper[bucket_quantity - 1] = usize::MAX;
+        RadixHeap {
+            upper,
+            buckets: vec![Vec::new(); bucket_quantity],
+            keys: vec![0; node_quantity],
+            positions: vec![None; node_quantity],
+            last: 0,
+            len: 0,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn contains(&self, node: usize) -> bool {
+        self.positions[node].is_some()
+    }
+
+    //key the node is queued with
+    pub fn key(&self, node: usize) -> Option<usize> {
+        self.positions[node].map(|_| self.keys[node])
+    }
+
+    //node must not be queued, key must be at least the last popped key
+    pub fn push(&mut self, node: usize, key: usize) {
+        assert!(key >= self.last, "radix heap key {key} below the last popped key {}", self.last);
+        assert!(!self.contains(node), "node {node} is already queued");
+        self.keys[node] = key;
+        self.len += 1;
+        self.insert(node, self.buckets.len() - 1);
+    }
+
+    //lowers the key of a queued node
+    pub fn decrease_key(&mut self, node: usize, key: usize) {
+        let (bucket, _) = self.positions[node].expect("decrease_key on a node that is not queued");
+        assert!(key >= self.last && key <= self.keys[node], "radix heap keys only decrease to at least the last popped key");
+        self.unlink(node);
+        self.keys[node] = key;
+        self.insert(node, bucket);
+    }
+
+    //(node, key) with the smallest key
+    pub fn pop(&mut self) -> Option<(usize, usize)> {
+        self.pop_with(&mut NoStats)
+    }
+
+    //pop recording the buckets looked at for the minimum
+    pub fn pop_with<S: Stats>(&mut self, stats: &mut S) -> Option<(usize, usize)> {
+        if self.len == 0 {
+            return None;
+        }
+        if self.buckets[0].is_empty() {
+            let bucket = self.buckets.iter().position(|bucket| !bucket.is_empty()).unwrap();
+            stats.record_many(Event::BucketScan, bucket + 1);
+            let minimum = self.buckets[bucket].iter().map(|node| self.keys[*node]).min().unwrap();
+            //split the range of the bucket among the ones below it, widths 1, 1, 2, 4, ... from the minimum
+            self.last = minimum;
+            self.upper[0] = minimum;
+            for i in 1..bucket {
+                self.upper[i] = minimum.saturating_add(span(i)).min(self.upper[bucket]);
+            }
+            for node in std::mem::take(&mut self.buckets[bucket]) {
+                self.positions[node] = None;
+                self.insert(node, bucket);
+            }
+        } else {
+            stats.record(Event::BucketScan);
+        }
+        let node = self.buckets[0].pop().unwrap();
+        self.positions[node] = None;
+        self.len -= 1;
+        Some((node, self.keys[node]))
+    }
+
+    //places node in the lowest bucket at or below start whose range holds its key
+    fn insert(&mut self, node: usize, start: usize) {
+        let key = self.keys[node];
+        let mut bucket = start;
+        while bucket > 0 && key <= self.upper[bucket - 1] {
+            bucket -= 1;
+        }
+        self.positions[node] = Some((bucket, self.buckets[bucket].len()));
+        self.buckets[bucket].push(node);
+    }
+
+    fn unlink(&mut self, node: usize) {
+        let (bucket, index) = self.positions[node].take().unwrap();
+        self.buckets[bucket].swap_remove(index);
+        if let Some(moved) = self.buckets[bucket].get(index) {
+            self.positions[*moved] = Some((bucket, index));
+        }
+    }
+}
+
+//2^i - 1, the largest key offset of bucket i
+fn span(i: usize) -> usize {
+    1usize.checked_shl(i as u32).map_or(usize::MAX, |power| power - 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cmp::Reverse;
+    use std::collections::BinaryHeap;
+
+    use crate::radix_heap::*;
+
+    #[test]
+    fn radix_heap_test() {
+        let mut heap = RadixHeap::new(10, 6);
+        heap.push(0, 0);
+        heap.push(1, 7);
+        heap.push(2, 10);
+        heap.push(3, 3);
+        assert_eq!(heap.pop(), Some((0, 0)));
+        heap.decrease_key(2, 4);
+        heap.push(4, 13);
+        assert_eq!(heap.key(2), Some(4));
+        assert_eq!(heap.pop(), Some((3, 3)));
+        assert_eq!(heap.pop(), Some((2, 4)));
+        heap.push(5, 4);
+        heap.decrease_key(4, 5);
+        assert_eq!(heap.pop(), Some((5, 4)));
+        assert_eq!(heap.pop(), Some((4, 5)));
+        assert_eq!(heap.pop(), Some((1, 7)));
+        assert!(heap.is_empty());
+        assert_eq!(heap.pop(), None);
+    }
+
+    //random monotone use like Dijkstra makes it, checked against a binary heap
+    #[test]
+    fn radix_heap_random_test() {
+        let mut state: u64 = 12345;
+        let mut next = |bound: usize| {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            (state % bound as u64) as usize
+        };
+        let max_step = 100;
+        let node_quantity = 300;
+        let mut heap = RadixHeap::new(max_step, node_quantity);
+        let mut reference = BinaryHeap::new();
+        let mut keys: Vec<Option<usize>> = vec![None; node_quantity];
+        let mut last = 0;
+        for _ in 0..20000 {
+            let node = next(node_quantity);
+            if next(3) > 0 {
+                let key = last + next(max_step + 1);
+                match heap.key(node) {
+                    None => heap.push(node, key),
+                    Some(old) if key < old => heap.decrease_key(node, key),
+                    Some(_) => continue,
+                }
+                keys[node] = Some(key);
+                reference.push(Reverse((key, node)));
+            } else {
+                while let Some(Reverse((key, node))) = reference.pop() {
+                    if keys[node] == Some(key) {
+                        let (popped, popped_key) = heap.pop().unwrap();
+                        assert_eq!(popped_key, key);
+                        assert_eq!(keys[popped], Some(key));
+                        keys[popped] = None;
+                        if popped != node {
+                            //same key, the reference gets it back
+                            reference.push(Reverse((key, node)));
+                        }
+                        last = key;
+                        break;
+                    }
+                }
+            }
+            assert_eq!(heap.len(), keys.iter().flatten().count());
+        }
+    }
+}
@@ -0,0 +1,445 @@
+use std::collections::VecDeque;
+
+use crate::stats::{self, Event, NoStats, Stats};
+use crate::Graph;
+
+//Graph whose arcs may cost less than zero, searched by label correcting algorithms instead of Dijkstra
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SignedGraph {
+    node_quantity: usize,
+    adj: Vec<Vec<(usize, i64)>>,
+}
+
+impl SignedGraph {
+    pub fn new(node_quantity: usize) -> Self {
+        SignedGraph { node_quantity, adj: vec![Vec::new(); node_quantity] }
+    }
+    //the new node gets id node_quantity
+    pub fn add_node(&mut self) {
+        self.node_quantity += 1;
+        self.adj.push(Vec::new());
+    }
+    pub fn add_edge(&mut self, from: usize, to: usize, cost: i64) {
+        self.adj[from - 1].push((to - 1, cost));
+    }
+    pub fn node_quantity(&self) -> usize {
+        self.node_quantity
+    }
+    pub fn arc_quantity(&self) -> usize {
+        self.adj.iter().map(|arcs| arcs.len()).sum()
+    }
+    //0-based tail, heads are 0-based as well
+    pub(crate) fn arcs(&self, node: usize) -> &[(usize, i64)] {
+        &self.adj[node]
+    }
+}
+
+impl From<&Graph> for SignedGraph {
+    fn from(graph: &Graph) -> Self {
+        let mut signed = SignedGraph::new(graph.node_quantity);
+        for from in 0..graph.node_quantity {
+            signed.adj[from] = graph.arcs(from).map(|(to, cost)| (to, cost as i64)).collect();
+        }
+        signed
+    }
+}
+
+//distances and predecessors are indexed by node - 1, predecessors hold node ids
+#[derive(Clone, Debug, PartialEq)]
+pub struct SignedPathTree {
+    pub source: usize,
+    pub distances: Vec<Option<i64>>,
+    pub predecessors: Vec<Option<usize>>,
+}
+
+impl SignedPathTree {
+    pub fn distance(&self, dst: usize) -> Option<i64> {
+        self.distances[dst - 1]
+    }
+
+    //nodes from the source to dst, None if dst is unreachable
+    pub fn path(&self, dst: usize) -> Option<Vec<usize>> {
+        self.distances[dst - 1]?;
+        let mut path = vec![dst];
+        let mut node = dst;
+        while let Some(previous) = self.predecessors[node - 1] {
+            path.push(previous);
+            node = previous;
+        }
+        path.reverse();
+        Some(path)
+    }
+}
+
+//nodes v1 .. vk of a cycle v1 -> v2 -> ... -> vk -> v1 reachable from the source, cost is negative
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NegativeCycle {
+    pub nodes: Vec<usize>,
+    pub cost: i64,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SpfaOrder {
+    //plain FIFO queue
+    Fifo,
+    //a node whose label is below the one at the front of the queue goes in front
+    SmallLabelFirst,
+}
+
+//Labels shared by the label correcting algorithms. A cycle of predecessors is always negative, so the
+//predecessor graph is searched for one after every node_quantity relaxations
+struct Labels {
+    distances: Vec<Option<i64>>,
+    predecessors: Vec<Option<usize>>,
+    relaxations: usize,
+}
+
+impl Labels {
+    fn new(node_quantity: usize, source: usize) -> Self {
+        let mut distances = vec![None; node_quantity];
+        distances[source] = Some(0);
+        Labels { distances, predecessors: vec![None; node_quantity], relaxations: 0 }
+    }
+
+    //true when the label of to dropped
+    fn relax<S: Stats>(&mut self, from: usize, to: usize, cost: i64, stats: &mut S) -> bool {
+        stats.record(Event::ScannedArc);
+        let new_distance = self.distances[from].unwrap() + cost;
+        if self.distances[to].is_some_and(|old| old <= new_distance) {
+            return false;
+        }
+        self.distances[to] = Some(new_distance);
+        self.predecessors[to] = Some(from);
+        self.relaxations += 1;
+        stats.record(Event::Relaxation);
+        true
+    }
+
+    fn periodic_check(&mut self, graph: &SignedGraph) -> Option<NegativeCycle> {
+        if self.relaxations < graph.node_quantity {
+            return None;
+        }
+        self.relaxations = 0;
+        self.predecessor_cycle(graph)
+    }
+
+    //walks up the predecessors from every node, a walk meeting itself closed a cycle
+    fn predecessor_cycle(&self, graph: &SignedGraph) -> Option<NegativeCycle> {
+        let mut walk = vec![usize::MAX; graph.node_quantity];
+        for start in 0..graph.node_quantity {
+            let mut node = start;
+            while walk[node] == usize::MAX {
+                walk[node] = start;
+                match self.predecessors[node] {
+                    Some(previous) => node = previous,
+                    None => break,
+                }
+            }
+            if walk[node] == start && self.predecessors[node].is_some() {
+                let mut cycle = vec![node];
+                let mut previous = self.predecessors[node].unwrap();
+                while previous != node {
+                    cycle.push(previous);
+                    previous = self.predecessors[previous].unwrap();
+                }
+                //the walk went against the arcs
+                cycle.reverse();
+                return Some(negative_cycle(graph, cycle));
+            }
+        }
+        None
+    }
+
+    fn into_tree(self, source: usize) -> SignedPathTree {
+        SignedPathTree {
+            source,
+            distances: self.distances,
+            predecessors: self.predecessors.into_iter().map(|previous| previous.map(|previous| previous + 1)).collect(),
+        }
+    }
+}
+
+//cost over the cheapest arc between consecutive nodes, 0-based nodes in, 1-based out
+fn negative_cycle(graph: &SignedGraph, cycle: Vec<usize>) -> NegativeCycle {
+    let cost = (0..cycle.len())
+        .map(|i| {
+            let (from, to) = (cycle[i], cycle[(i + 1) % cycle.len()]);
+            graph.arcs(from).iter().filter(|(next, _)| *next == to).map(|(_, cost)| *cost).min().unwrap()
+        })
+        .sum();
+    NegativeCycle { nodes: cycle.into_iter().map(|node| node + 1).collect(), cost }
+}
+
+impl SignedGraph {
+    //node_quantity - 1 rounds over every arc, stopping early once a round changes nothing.
+    //A label still dropping in round node_quantity lies behind a negative cycle
+    pub fn bellman_ford(&self, src: usize) -> Result<SignedPathTree, NegativeCycle> {
+        self.bellman_ford_with(src, &mut NoStats)
+    }
+
+    pub fn bellman_ford_stats<S: Stats>(&self, src: usize) -> (Result<SignedPathTree, NegativeCycle>, S) {
+        stats::collect(|stats| self.bellman_ford_with(src, stats))
+    }
+
+    fn bellman_ford_with<S: Stats>(&self, src: usize, stats: &mut S) -> Result<SignedPathTree, NegativeCycle> {
+        let mut labels = Labels::new(self.node_quantity, src - 1);
+        let mut changed = None;
+        for _ in 0..self.node_quantity {
+            changed = None;
+            for from in 0..self.node_quantity {
+                if labels.distances[from].is_none() {
+                    continue;
+                }
+                stats.record(Event::ScannedNode);
+                for (to, cost) in &self.adj[from] {
+                    if labels.relax(from, *to, *cost, stats) {
+                        changed = Some(*to);
+                    }
+                }
+            }
+            if changed.is_none() {
+                return Ok(labels.into_tree(src));
+            }
+        }
+        //node_quantity steps back from a node relaxed in the last round always end on the cycle
+        let mut node = changed.unwrap();
+        for _ in 0..self.node_quantity {
+            node = labels.predecessors[node].unwrap();
+        }
+        let mut cycle = vec![node];
+        let mut previous = labels.predecessors[node].unwrap();
+        while previous != node {
+            cycle.push(previous);
+            previous = labels.predecessors[previous].unwrap();
+        }
+        cycle.reverse();
+        Err(negative_cycle(self, cycle))
+    }
+
+    //Bellman-Ford scanning only nodes whose label dropped, kept in a queue
+    pub fn spfa(&self, src: usize, order: SpfaOrder) -> Result<SignedPathTree, NegativeCycle> {
+        self.spfa_with(src, order, &mut NoStats)
+    }
+
+    pub fn spfa_stats<S: Stats>(&self, src: usize, order: SpfaOrder) -> (Result<SignedPathTree, NegativeCycle>, S) {
+        stats::collect(|stats| self.spfa_with(src, order, stats))
+    }
+
+    fn spfa_with<S: Stats>(&self, src: usize, order: SpfaOrder, stats: &mut S) -> Result<SignedPathTree, NegativeCycle> {
+        let mut labels = Labels::new(self.node_quantity, src - 1);
+        let mut queue = VecDeque::from([src - 1]);
+        stats.record(Event::Push);
+        let mut queued = vec![false; self.node_quantity];
+        queued[src - 1] = true;
+        while let Some(from) = queue.pop_front() {
+            stats.record(Event::Pop);
+            stats.record(Event::ScannedNode);
+            queued[from] = false;
+            for (to, cost) in &self.adj[from] {
+                if !labels.relax(from, *to, *cost, stats) || queued[*to] {
+                    continue;
+                }
+                queued[*to] = true;
+                stats.record(Event::Push);
+                let in_front = order == SpfaOrder::SmallLabelFirst
+                    && queue.front().is_some_and(|front| labels.distances[*to] < labels.distances[*front]);
+                if in_front {
+                    queue.push_front(*to);
+                } else {
+                    queue.push_back(*to);
+                }
+            }
+            if let Some(cycle) = labels.periodic_check(self) {
+                return Err(cycle);
+            }
+        }
+        Ok(labels.into_tree(src))
+    }
+
+    //Goldberg-Radzik: every pass takes the nodes whose label dropped, keeps those with an arc of negative
+    //reduced cost and scans everything reachable from them over arcs of reduced cost <= 0 in topological order
+    pub fn goldberg_radzik(&self, src: usize) -> Result<SignedPathTree, NegativeCycle> {
+        self.goldberg_radzik_with(src, &mut NoStats)
+    }
+
+    pub fn goldberg_radzik_stats<S: Stats>(&self, src: usize) -> (Result<SignedPathTree, NegativeCycle>, S) {
+        stats::collect(|stats| self.goldberg_radzik_with(src, stats))
+    }
+
+    fn goldberg_radzik_with<S: Stats>(&self, src: usize, stats: &mut S) -> Result<SignedPathTree, NegativeCycle> {
+        let mut labels = Labels::new(self.node_quantity, src - 1);
+        let mut dropped = vec![src - 1];
+        let mut in_pass = vec![false; self.node_quantity];
+        let mut is_dropped = vec![false; self.node_quantity];
+        //unlabeled heads are infinitely far, arcs out of unlabeled tails wait for the next pass
+        let reduced = |labels: &Labels, from: usize, to: usize, cost: i64| match (labels.distances[from], labels.distances[to]) {
+            (None, _) => i64::MAX,
+            (Some(_), None) => i64::MIN,
+            (Some(from_distance), Some(to_distance)) => from_distance + cost - to_distance,
+        };
+        while !dropped.is_empty() {
+            //depth first search over admissible arcs, the reversed post order is topological
+            let mut order = Vec::new();
+            for start in std::mem::take(&mut dropped) {
+                is_dropped[start] = false;
+                let has_negative = self.adj[start].iter().any(|(to, cost)| reduced(&labels, start, *to, *cost) < 0);
+                if in_pass[start] || !has_negative {
+                    continue;
+                }
+                in_pass[start] = true;
+                let mut stack = vec![(start, 0)];
+                while let Some((node, next)) = stack.pop() {
+                    if let Some((to, cost)) = self.adj[node].get(next) {
+                        stack.push((node, next + 1));
+                        if !in_pass[*to] && reduced(&labels, node, *to, *cost) <= 0 {
+                            in_pass[*to] = true;
+                            stack.push((*to, 0));
+                        }
+                    } else {
+                        order.push(node);
+                    }
+                }
+            }
+            for &from in order.iter().rev() {
+                in_pass[from] = false;
+                if labels.distances[from].is_none() {
+                    continue;
+                }
+                stats.record(Event::ScannedNode);
+                for (to, cost) in &self.adj[from] {
+                    if labels.relax(from, *to, *cost, stats) && !is_dropped[*to] {
+                        is_dropped[*to] = true;
+                        dropped.push(*to);
+                    }
+                }
+            }
+            if let Some(cycle) = labels.periodic_check(self) {
+                return Err(cycle);
+            }
+        }
+        Ok(labels.into_tree(src))
+    }
+}
+
+//Finds x with x[j] - x[i] <= c for every (i, j, c), variables are 1-based. Every constraint is an arc
+//i -> j of cost c and a virtual source reaches all variables at cost 0. Infeasible systems return the
+//negative cycle of constraints that contradict each other
+pub fn solve_difference_constraints(variable_quantity: usize, constraints: &[(usize, usize, i64)]) -> Result<Vec<i64>, NegativeCycle> {
+    let mut graph = SignedGraph::new(variable_quantity + 1);
+    for variable in 1..=variable_quantity {
+        graph.add_edge(variable_quantity + 1, variable, 0);
+    }
+    for &(i, j, c) in constraints {
+        graph.add_edge(i, j, c);
+    }
+    let tree = graph.goldberg_radzik(variable_quantity + 1)?;
+    Ok(tree.distances[..variable_quantity].iter().map(|distance| distance.unwrap()).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::random_graph;
+    use crate::signed::*;
+    use crate::stats::SearchStats;
+
+    fn solvers(graph: &SignedGraph, src: usize) -> [Result<SignedPathTree, NegativeCycle>; 4] {
+        [
+            graph.bellman_ford(src),
+            graph.spfa(src, SpfaOrder::Fifo),
+            graph.spfa(src, SpfaOrder::SmallLabelFirst),
+            graph.goldberg_radzik(src),
+        ]
+    }
+
+    fn check_cycle(graph: &SignedGraph, cycle: &NegativeCycle) {
+        assert!(cycle.cost < 0);
+        let mut cost = 0;
+        for i in 0..cycle.nodes.len() {
+            let (from, to) = (cycle.nodes[i] - 1, cycle.nodes[(i + 1) % cycle.nodes.len()] - 1);
+            cost += graph.arcs(from).iter().filter(|(next, _)| *next == to).map(|(_, cost)| *cost).min().unwrap();
+        }
+        assert_eq!(cost, cycle.cost);
+    }
+
+    #[test]
+    fn signed_test() {
+        let mut graph = SignedGraph::new(5);
+        graph.add_edge(1, 2, 6);
+        graph.add_edge(1, 4, 7);
+        graph.add_edge(2, 3, 5);
+        graph.add_edge(2, 4, 8);
+        graph.add_edge(2, 5, -4);
+        graph.add_edge(3, 2, -2);
+        graph.add_edge(4, 3, -3);
+        graph.add_edge(4, 5, 9);
+        graph.add_edge(5, 1, 2);
+        graph.add_edge(5, 3, 7);
+        for result in solvers(&graph, 1) {
+            let tree = result.unwrap();
+            assert_eq!(tree.distances, vec![Some(0), Some(2), Some(4), Some(7), Some(-2)]);
+            assert_eq!(tree.path(5), Some(vec![1, 4, 3, 2, 5]));
+        }
+        let (result, spfa) = graph.spfa_stats::<SearchStats>(1, SpfaOrder::Fifo);
+        assert_eq!(result, graph.spfa(1, SpfaOrder::Fifo));
+        assert_eq!((spfa.pushes, spfa.pops), (spfa.scanned_nodes, spfa.scanned_nodes));
+        assert!(spfa.relaxations >= 4 && spfa.relaxations <= spfa.scanned_arcs);
+        //every round of Bellman-Ford looks at every arc of a labelled node, the last round changes nothing
+        let (result, bellman_ford) = graph.bellman_ford_stats::<SearchStats>(1);
+        assert_eq!(result, graph.bellman_ford(1));
+        assert_eq!(bellman_ford.scanned_arcs % graph.arc_quantity(), 0);
+        assert!(bellman_ford.relaxations < bellman_ford.scanned_arcs);
+        let (result, goldberg_radzik) = graph.goldberg_radzik_stats::<SearchStats>(1);
+        assert_eq!(result, graph.goldberg_radzik(1));
+        assert!(goldberg_radzik.relaxations >= 4);
+        graph.add_edge(3, 4, 1);
+        for result in solvers(&graph, 1) {
+            let cycle = result.unwrap_err();
+            check_cycle(&graph, &cycle);
+        }
+        //a negative cycle the source cannot reach does not count
+        let mut unreachable = SignedGraph::new(3);
+        unreachable.add_edge(2, 3, -1);
+        unreachable.add_edge(3, 2, -1);
+        for result in solvers(&unreachable, 1) {
+            assert_eq!(result.unwrap().distances, vec![Some(0), None, None]);
+        }
+    }
+
+    #[test]
+    fn signed_random_test() {
+        for seed in 1..30 {
+            let graph = random_graph(40, 120, 30, seed);
+            let signed = SignedGraph::from(&graph);
+            for src in [1, 20] {
+                for result in solvers(&signed, src) {
+                    assert_eq!(result.unwrap().distances, graph.djikstra_classic_ss(src).iter().map(|d| d.map(|d| d as i64)).collect::<Vec<_>>());
+                }
+            }
+            //shift costs down so that some cycles turn negative, all solvers have to agree on feasibility
+            let mut shifted = SignedGraph::new(40);
+            for (from, arcs) in signed.adj.iter().enumerate() {
+                for (to, cost) in arcs {
+                    shifted.add_edge(from + 1, to + 1, cost - 8);
+                }
+            }
+            let results = solvers(&shifted, 1);
+            for result in &results {
+                assert_eq!(result.is_ok(), results[0].is_ok());
+                match result {
+                    Ok(tree) => assert_eq!(tree.distances, results[0].as_ref().unwrap().distances),
+                    Err(cycle) => check_cycle(&shifted, cycle),
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn difference_constraints_test() {
+        //x2 - x1 <= 3, x3 - x2 <= -2, x1 - x3 <= 0
+        let x = solve_difference_constraints(3, &[(1, 2, 3), (2, 3, -2), (3, 1, 0)]).unwrap();
+        assert!(x[1] - x[0] <= 3 && x[2] - x[1] <= -2 && x[0] - x[2] <= 0);
+        let cycle = solve_difference_constraints(3, &[(1, 2, 3), (2, 3, -2), (3, 1, -2)]).unwrap_err();
+        assert_eq!(cycle.cost, -1);
+        assert_eq!(cycle.nodes.len(), 3);
+    }
+}
@@ -0,0 +1,96 @@
+//one step of a search, as counted by a Stats implementation
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Event {
+    Push,
+    //an entry taken off a queue, searches on a lazy BinaryHeap count the stale ones too
+    Pop,
+    DecreaseKey,
+    //an arc that lowered a label
+    Relaxation,
+    ScannedNode,
+    ScannedArc,
+    //a bucket looked at by a bucket queue while searching for the minimum, empty or not
+    BucketScan,
+}
+
+//Instrumentation every search is generic over. Both methods do nothing by default, so with NoStats the
+//calls compile away and timed runs pay nothing; SearchStats counts every event
+pub trait Stats: Default {
+    fn record(&mut self, event: Event) {
+        self.record_many(event, 1);
+    }
+
+    fn record_many(&mut self, _event: Event, _times: usize) {}
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct NoStats;
+
+impl Stats for NoStats {}
+
+//operation counts of one search, or of several added up
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SearchStats {
+    pub pushes: usize,
+    pub pops: usize,
+    pub decrease_keys: usize,
+    pub relaxations: usize,
+    pub scanned_nodes: usize,
+    pub scanned_arcs: usize,
+    pub bucket_scans: usize,
+}
+
+impl SearchStats {
+    pub fn add(&mut self, other: &SearchStats) {
+        self.pushes += other.pushes;
+        self.pops += other.pops;
+        self.decrease_keys += other.decrease_keys;
+        self.relaxations += other.relaxations;
+        self.scanned_nodes += other.scanned_nodes;
+        self.scanned_arcs += other.scanned_arcs;
+        self.bucket_scans += other.bucket_scans;
+    }
+}
+
+impl Stats for SearchStats {
+    fn record_many(&mut self, event: Event, times: usize) {
+        let counter = match event {
+            Event::Push => &mut self.pushes,
+            Event::Pop => &mut self.pops,
+            Event::DecreaseKey => &mut self.decrease_keys,
+            Event::Relaxation => &mut self.relaxations,
+            Event::ScannedNode => &mut self.scanned_nodes,
+            Event::ScannedArc => &mut self.scanned_arcs,
+            Event::BucketScan => &mut self.bucket_scans,
+        };
+        *counter += times;
+    }
+}
+
+//runs a search on fresh stats and returns them next to its result
+pub fn collect<S: Stats, T>(search: impl FnOnce(&mut S) -> T) -> (T, S) {
+    let mut stats = S::default();
+    let result = search(&mut stats);
+    (result, stats)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::stats::*;
+
+    #[test]
+    fn stats_test() {
+        let (answer, stats) = collect(|stats: &mut SearchStats| {
+            stats.record(Event::Push);
+            stats.record_many(Event::ScannedArc, 3);
+            stats.record(Event::BucketScan);
+            42
+        });
+        assert_eq!(answer, 42);
+        assert_eq!(stats, SearchStats { pushes: 1, scanned_arcs: 3, bucket_scans: 1, ..SearchStats::default() });
+        let mut total = stats;
+        total.add(&stats);
+        assert_eq!((total.pushes, total.scanned_arcs, total.pops), (2, 6, 0));
+        assert_eq!(collect(|stats: &mut NoStats| stats.record(Event::Pop)).1, NoStats);
+    }
+}
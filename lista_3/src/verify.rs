@@ -0,0 +1,209 @@
+use std::fmt;
+
+use crate::queue::QueueKind;
+use crate::signed::{SignedGraph, SignedPathTree, SpfaOrder};
+use crate::{Algorithm, Graph, ShortestPathTree};
+
+pub type SsAlgorithm = (String, Box<dyn Fn(&Graph, usize) -> ShortestPathTree + Sync>);
+
+//First thing found wrong, nodes are ids. The reference is classic Dijkstra, whose own certificate is
+//checked before anything is compared with it
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Mismatch {
+    Distance { algorithm: String, source: usize, node: usize, expected: Option<usize>, found: Option<usize> },
+    //d[to] > d[from] + cost, an unreached to counts as infinitely far
+    NotRelaxed { algorithm: String, source: usize, from: usize, to: usize, cost: usize },
+    //reached node other than the source whose predecessor arc is missing or not tight
+    NoTightParent { algorithm: String, source: usize, node: usize },
+    //following predecessors from node never gets back to the source
+    DetachedParent { algorithm: String, source: usize, node: usize },
+    SourceDistance { algorithm: String, source: usize, found: Option<usize> },
+}
+
+impl fmt::Display for Mismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Mismatch::Distance { algorithm, source, node, expected, found } => {
+                write!(f, "{algorithm} from {source}: node {node} at {found:?}, expected {expected:?}")
+            },
+            Mismatch::NotRelaxed { algorithm, source, from, to, cost } => {
+                write!(f, "{algorithm} from {source}: arc {from} -> {to} of cost {cost} can still be relaxed")
+            },
+            Mismatch::NoTightParent { algorithm, source, node } => {
+                write!(f, "{algorithm} from {source}: node {node} has no tight parent arc")
+            },
+            Mismatch::DetachedParent { algorithm, source, node } => {
+                write!(f, "{algorithm} from {source}: parents of node {node} do not lead back to the source")
+            },
+            Mismatch::SourceDistance { algorithm, source, found } => {
+                write!(f, "{algorithm} from {source}: source at {found:?}")
+            },
+        }
+    }
+}
+
+impl std::error::Error for Mismatch {}
+
+//every single source search of the crate, new ones are added here to be verified
+pub fn ss_algorithms() -> Vec<SsAlgorithm> {
+    let mut algorithms: Vec<SsAlgorithm> = Vec::new();
+    for algorithm in Algorithm::ALL {
+        algorithms.push((algorithm.name().to_string(), Box::new(move |graph, src| graph.shortest_path_tree(algorithm, src))));
+    }
+    for kind in QueueKind::ALL {
+        algorithms.push((format!("queue-{}", kind.name()), Box::new(move |graph, src| graph.queue_tree(kind, src, None))));
+    }
+    algorithms.push(("delta-stepping".to_string(), Box::new(|graph, src| graph.delta_stepping_tree(src, graph.default_delta()))));
+    algorithms.push(("bellman-ford".to_string(), Box::new(|graph, src| unsigned(SignedGraph::from(graph).bellman_ford(src)))));
+    algorithms.push(("spfa".to_string(), Box::new(|graph, src| unsigned(SignedGraph::from(graph).spfa(src, SpfaOrder::Fifo)))));
+    algorithms.push(("spfa-slf".to_string(), Box::new(|graph, src| unsigned(SignedGraph::from(graph).spfa(src, SpfaOrder::SmallLabelFirst)))));
+    algorithms.push(("goldberg-radzik".to_string(), Box::new(|graph, src| unsigned(SignedGraph::from(graph).goldberg_radzik(src)))));
+    algorithms
+}
+
+//costs of a Graph are never negative, so neither is a cycle
+fn unsigned<E: fmt::Debug>(result: Result<SignedPathTree, E>) -> ShortestPathTree {
+    let tree = result.expect("no negative cycle without negative arcs");
+    ShortestPathTree {
+        source: tree.source,
+        distances: tree.distances.into_iter().map(|distance| distance.map(|distance| distance as usize)).collect(),
+        predecessors: tree.predecessors,
+    }
+}
+
+//Optimality certificate of a shortest path tree: the source at 0, no arc left to relax, and every other
+//reached node with a tight arc from its predecessor, following which leads back to the source
+pub fn check_tree(graph: &Graph, algorithm: &str, tree: &ShortestPathTree) -> Result<(), Mismatch> {
+    let source = tree.source;
+    let algorithm = algorithm.to_string();
+    if tree.distances[source - 1] != Some(0) {
+        return Err(Mismatch::SourceDistance { algorithm, source, found: tree.distances[source - 1] });
+    }
+    for from in 0..graph.node_quantity {
+        let Some(distance) = tree.distances[from] else {
+            continue;
+        };
+        for (to, cost) in graph.arcs(from) {
+            if tree.distances[to].is_none_or(|to_distance| to_distance > distance + cost) {
+                return Err(Mismatch::NotRelaxed { algorithm, source, from: from + 1, to: to + 1, cost });
+            }
+        }
+    }
+    for node in 0..graph.node_quantity {
+        let Some(distance) = tree.distances[node] else {
+            continue;
+        };
+        if node == source - 1 {
+            continue;
+        }
+        let tight = tree.predecessors[node].is_some_and(|parent| {
+            tree.distances[parent - 1]
+                .is_some_and(|parent_distance| graph.arcs(parent - 1).any(|(to, cost)| to == node && parent_distance + cost == distance))
+        });
+        if !tight {
+            return Err(Mismatch::NoTightParent { algorithm, source, node: node + 1 });
+        }
+    }
+    //a tight cycle of zero cost arcs would pass the checks above without reaching the source
+    let mut leads_to_source = vec![None; graph.node_quantity];
+    leads_to_source[source - 1] = Some(true);
+    for start in 0..graph.node_quantity {
+        if tree.distances[start].is_none() || leads_to_source[start].is_some() {
+            continue;
+        }
+        let mut walk = vec![start];
+        let mut node = start;
+        let found = loop {
+            //inside this walk is Some(false) until the walk ends
+            leads_to_source[node] = Some(false);
+            let parent = tree.predecessors[node].unwrap() - 1;
+            match leads_to_source[parent] {
+                Some(true) => break true,
+                Some(false) => break false,
+                None => {
+                    walk.push(parent);
+                    node = parent;
+                },
+            }
+        };
+        for node in walk {
+            leads_to_source[node] = Some(found);
+        }
+        if !found {
+            return Err(Mismatch::DetachedParent { algorithm, source, node: start + 1 });
+        }
+    }
+    Ok(())
+}
+
+//Runs the algorithms from every source, checks their certificates and diffs their distances against
+//classic Dijkstra. Stops at the first mismatch
+pub fn verify(graph: &Graph, algorithms: &[SsAlgorithm], sources: &[usize]) -> Result<(), Mismatch> {
+    for &source in sources {
+        let reference = graph.djikstra_classic_tree(source);
+        check_tree(graph, "reference", &reference)?;
+        for (name, run) in algorithms {
+            let tree = run(graph, source);
+            for node in 0..graph.node_quantity {
+                if tree.distances[node] != reference.distances[node] {
+                    return Err(Mismatch::Distance {
+                        algorithm: name.clone(),
+                        source,
+                        node: node + 1,
+                        expected: reference.distances[node],
+                        found: tree.distances[node],
+                    });
+                }
+            }
+            check_tree(graph, name, &tree)?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::random_graph;
+    use crate::verify::*;
+
+    #[test]
+    fn verify_test() {
+        let algorithms = ss_algorithms();
+        for seed in 1..10 {
+            let graph = random_graph(80, 300, 40, seed);
+            assert_eq!(verify(&graph, &algorithms, &[1, 40, 80]), Ok(()));
+        }
+    }
+
+    #[test]
+    fn mismatch_test() {
+        let mut graph = Graph::new(4);
+        graph.add_edge(1, 2, 2);
+        graph.add_edge(2, 3, 3);
+        graph.add_edge(1, 3, 7);
+        graph.add_edge(3, 4, 0);
+        graph.add_edge(4, 3, 0);
+        let tree = graph.djikstra_classic_tree(1);
+        assert_eq!(check_tree(&graph, "classic", &tree), Ok(()));
+
+        let mut wrong = tree.clone();
+        wrong.distances[2] = Some(6);
+        assert_eq!(check_tree(&graph, "wrong", &wrong), Err(Mismatch::NotRelaxed { algorithm: "wrong".to_string(), source: 1, from: 2, to: 3, cost: 3 }));
+        let broken: Vec<SsAlgorithm> = vec![("broken".to_string(), Box::new(|graph, src| {
+            let mut tree = graph.djikstra_classic_tree(src);
+            tree.distances[3] = None;
+            tree
+        }))];
+        let mismatch = verify(&graph, &broken, &[1]).unwrap_err();
+        assert_eq!(mismatch, Mismatch::Distance { algorithm: "broken".to_string(), source: 1, node: 4, expected: Some(5), found: None });
+        assert_eq!(mismatch.to_string(), "broken from 1: node 4 at None, expected Some(5)");
+
+        let mut loose = tree.clone();
+        loose.predecessors[2] = Some(1);
+        assert_eq!(check_tree(&graph, "loose", &loose), Err(Mismatch::NoTightParent { algorithm: "loose".to_string(), source: 1, node: 3 }));
+        //3 and 4 hang on each other over the zero cost arcs
+        let mut detached = tree;
+        detached.predecessors[2] = Some(4);
+        assert_eq!(check_tree(&graph, "detached", &detached), Err(Mismatch::DetachedParent { algorithm: "detached".to_string(), source: 1, node: 3 }));
+    }
+}
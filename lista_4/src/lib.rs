@@ -5,6 +5,69 @@ use rand_pcg::Pcg64;
 const NIL: usize = 0;
 const INF: usize = usize::MAX;
 
+// Instrumentation the flow and matching algorithms are generic over. Every method does nothing by default,
+// so NoStats costs nothing in timed runs and FlowStats counts
+pub trait Stats: Default {
+    fn push(&mut self) {}
+    fn pop(&mut self) {}
+    fn scanned_arc(&mut self) {}
+    // one BFS building distance labels or a level graph
+    fn phase(&mut self) {}
+    // length in arcs of an augmenting path that was used
+    fn augmenting_path(&mut self, _length: usize) {}
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct NoStats;
+
+impl Stats for NoStats {}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FlowStats {
+    // BFS queue operations
+    pub pushes: usize,
+    pub pops: usize,
+    pub scanned_arcs: usize,
+    pub phases: usize,
+    // in the order the paths were augmented
+    pub path_lengths: Vec<usize>,
+}
+
+impl FlowStats {
+    pub fn augmenting_paths(&self) -> usize {
+        self.path_lengths.len()
+    }
+
+    pub fn longest_path(&self) -> usize {
+        self.path_lengths.iter().copied().max().unwrap_or(0)
+    }
+
+    pub fn mean_path_length(&self) -> f64 {
+        if self.path_lengths.is_empty() {
+            return 0.0;
+        }
+        self.path_lengths.iter().sum::<usize>() as f64 / self.path_lengths.len() as f64
+    }
+}
+
+impl Stats for FlowStats {
+    fn push(&mut self) {
+        self.pushes += 1;
+    }
+    fn pop(&mut self) {
+        self.pops += 1;
+    }
+    fn scanned_arc(&mut self) {
+        self.scanned_arcs += 1;
+    }
+    fn phase(&mut self) {
+        self.phases += 1;
+    }
+    fn augmenting_path(&mut self, length: usize) {
+        self.path_lengths.push(length);
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct Bigraph {
     adj: Vec<Vec<usize>>,
@@ -26,50 +89,62 @@ impl Bigraph {
         Bigraph { adj, n, m }
     }
     pub fn hopcroft_karp(&self) -> usize {
+        self.hopcroft_karp_stats::<NoStats>().0
+    }
+    pub fn hopcroft_karp_stats<S: Stats>(&self) -> (usize, S) {
+        let mut stats = S::default();
         let mut pair_u: Vec<usize> = vec![NIL; self.n + 1];
         let mut pair_v: Vec<usize> = vec![NIL; self.m + 1];
         let mut dist: Vec<usize> = vec![usize::MAX; self.n + 1];
         let mut matching: usize = 0;
         
-        while self.bfs(&mut pair_u, &mut pair_v, &mut dist) {
+        while self.bfs(&mut pair_u, &mut pair_v, &mut dist, &mut stats) {
             for u in 1..=self.n {
-                if pair_u[u] == NIL && self.dfs(u, &mut pair_u, &mut pair_v, &mut dist) {
+                if pair_u[u] == NIL && self.dfs(u, &mut pair_u, &mut pair_v, &mut dist, &mut stats) {
                     matching += 1;
+                    // every path of a phase alternates through dist[NIL] nodes of U
+                    stats.augmenting_path(2 * dist[NIL] - 1);
                 }
             }
         }
 
-        matching
+        (matching, stats)
     }
-    fn bfs(&self, pair_u: &mut Vec<usize>,  pair_v: &mut Vec<usize>, dist: &mut Vec<usize>) -> bool{
+    fn bfs<S: Stats>(&self, pair_u: &mut Vec<usize>,  pair_v: &mut Vec<usize>, dist: &mut Vec<usize>, stats: &mut S) -> bool{
+        stats.phase();
         let mut queue: VecDeque<usize> = VecDeque::new();
 
         for u in 1..=self.n {
             if pair_u[u] == NIL {
                 dist[u] = 0;
                 queue.push_back(u);
+                stats.push();
             } else {
                 dist[u] = INF;
             }
         }
         dist[NIL] = INF;
         while let Some(u) = queue.pop_front() {
+            stats.pop();
             if dist[u] < dist[NIL] {
                 for v in &self.adj[u] {
+                    stats.scanned_arc();
                     if dist[pair_v[*v]] == INF {
                         dist[pair_v[*v]] = dist[u] + 1;
                         queue.push_back(pair_v[*v]);
+                        stats.push();
                     }
                 }
             }
         }
         dist[NIL] != INF
     }
-    fn dfs(&self, u: usize, pair_u: &mut Vec<usize>, pair_v: &mut Vec<usize>, dist: &mut Vec<usize>) -> bool {
+    fn dfs<S: Stats>(&self, u: usize, pair_u: &mut Vec<usize>, pair_v: &mut Vec<usize>, dist: &mut Vec<usize>, stats: &mut S) -> bool {
         if u != NIL {
             for v in &self.adj[u] {
+                stats.scanned_arc();
                 if dist[pair_v[*v]] == dist[u] + 1 {
-                    if self.dfs(pair_v[*v], pair_u, pair_v, dist) {
+                    if self.dfs(pair_v[*v], pair_u, pair_v, dist, stats) {
                         pair_v[*v] = u;
                         pair_u[u] = *v;
                         return true;
@@ -153,20 +228,25 @@ objective_value(max_flow)\n";
         printable
     }
 
-    fn bfs(&self, source: usize, target: usize, parent: &mut Vec<Option<usize>>) -> bool {
+    fn bfs<S: Stats>(&self, source: usize, target: usize, parent: &mut Vec<Option<usize>>, stats: &mut S) -> bool {
+        stats.phase();
         let mut visited = vec![false; self.node_quantity];
         let mut queue = Vec::new();
         queue.push(source);
+        stats.push();
         visited[source] = true;
         parent[source] = None;
 
         while !queue.is_empty() {
             let u = queue.remove(0);
+            stats.pop();
 
             for edge in &self.adj[u]{
+                stats.scanned_arc();
                 let v = edge.to;
                 if !visited[v] && edge.residual_capacity > 0 {
                     queue.push(v);
+                    stats.push();
                     parent[v] = Some(u);
                     visited[v] = true;
                 }
@@ -177,20 +257,28 @@ objective_value(max_flow)\n";
     }
 
     pub fn edmonds_karp(&mut self, source: usize, target: usize) -> (isize, usize) {
+        self.edmonds_karp_stats::<NoStats>(source, target).0
+    }
+
+    pub fn edmonds_karp_stats<S: Stats>(&mut self, source: usize, target: usize) -> ((isize, usize), S) {
+        let mut stats = S::default();
         let mut parent = vec![None; self.node_quantity];
         let mut max_flow = 0;
         let mut augmenting_paths = 0;
 
-        while self.bfs(source, target, &mut parent) {
+        while self.bfs(source, target, &mut parent, &mut stats) {
             augmenting_paths += 1;
             let mut path_flow = std::isize::MAX;
+            let mut path_length = 0;
 
             let mut v = target;
             while let Some(u) = parent[v] {
                 let edge = self.adj[u].iter_mut().find(|e| e.to == v).unwrap();
                 path_flow = path_flow.min(edge.residual_capacity);
+                path_length += 1;
                 v = u;
             }
+            stats.augmenting_path(path_length);
 
             v = target;
             while let Some(u) = parent[v] {
@@ -208,11 +296,12 @@ objective_value(max_flow)\n";
             max_flow += path_flow;
         }
 
-        (max_flow, augmenting_paths)
+        ((max_flow, augmenting_paths), stats)
     }
 
 
-    fn d_bfs(&self, source: usize, sink: usize, level: &mut Vec<usize>) -> bool {
+    fn d_bfs<S: Stats>(&self, source: usize, sink: usize, level: &mut Vec<usize>, stats: &mut S) -> bool {
+        stats.phase();
         for i in 0..self.node_quantity {
             level[i] = std::usize::MAX;
         }
@@ -220,15 +309,19 @@ objective_value(max_flow)\n";
     
         let mut queue = VecDeque::new();
         queue.push_back(source);
+        stats.push();
         let mut visited_sink = false;
     
         while !queue.is_empty() {
             let u = queue.pop_front().unwrap();
+            stats.pop();
     
             for edge in &self.adj[u] {
+                stats.scanned_arc();
                 if edge.residual_capacity > 0 && level[edge.to] == std::usize::MAX {
                     level[edge.to] = level[u] + 1;
                     queue.push_back(edge.to);
+                    stats.push();
                     if edge.to == sink {
                         visited_sink = true;
                     }
@@ -240,7 +333,7 @@ objective_value(max_flow)\n";
     }
 
     // Uses DFS to find blocking flow.
-    fn dfs(&mut self, u: usize, min_edge: isize, sink: usize, level: &Vec<usize>, start: &mut Vec<usize>) -> isize {
+    fn dfs<S: Stats>(&mut self, u: usize, min_edge: isize, sink: usize, level: &Vec<usize>, start: &mut Vec<usize>, stats: &mut S) -> isize {
         if u == sink || min_edge == 0 {
             return min_edge;
         }
@@ -248,9 +341,10 @@ objective_value(max_flow)\n";
         while start[u] < self.adj[u].len() {
             let edge_index = start[u];
             let edge = self.adj[u][edge_index].clone();  // Clone edge to avoid borrow checker issues
+            stats.scanned_arc();
 
             if level[edge.to] == level[u] + 1 {
-                let flow = self.dfs(edge.to, min_edge.min(edge.residual_capacity), sink, level, start);
+                let flow = self.dfs(edge.to, min_edge.min(edge.residual_capacity), sink, level, start, stats);
 
                 if flow > 0 {
                     let actual_edge = &mut self.adj[u][edge_index];
@@ -272,26 +366,33 @@ objective_value(max_flow)\n";
     }
 
     pub fn dinic(&mut self, source: usize, sink: usize) -> (isize, usize) {
+        self.dinic_stats::<NoStats>(source, sink).0
+    }
+
+    pub fn dinic_stats<S: Stats>(&mut self, source: usize, sink: usize) -> ((isize, usize), S) {
+        let mut stats = S::default();
         let mut max_flow = 0;
         let mut level = vec![0; self.node_quantity];
         let mut start = vec![0; self.node_quantity];
         let mut num_augmenting_paths = 0;
 
-        while self.d_bfs(source, sink, &mut level) {
+        while self.d_bfs(source, sink, &mut level, &mut stats) {
             for i in 0..self.node_quantity {
                 start[i] = 0;
             }
 
-            let mut flow = self.dfs(source, std::isize::MAX, sink, &level, &mut start);
+            let mut flow = self.dfs(source, std::isize::MAX, sink, &level, &mut start, &mut stats);
 
             while flow != 0 {
                 num_augmenting_paths += 1;
+                // paths of the level graph all have level[sink] arcs
+                stats.augmenting_path(level[sink]);
                 max_flow += flow;
-                flow = self.dfs(source, std::isize::MAX, sink, &level, &mut start);
+                flow = self.dfs(source, std::isize::MAX, sink, &level, &mut start, &mut stats);
             }
         }
 
-        (max_flow, num_augmenting_paths)
+        ((max_flow, num_augmenting_paths), stats)
     }
 }
 
@@ -360,6 +461,27 @@ mod tests {
         n.dinic(0, 7);
     }
 
+    #[test]
+    fn flow_stats_test(){
+        let mut edmonds_karp = Hypercube::new(4);
+        let mut dinic = edmonds_karp.clone();
+        let mut plain = edmonds_karp.clone();
+        let (flow, stats) = edmonds_karp.edmonds_karp_stats::<FlowStats>(0, 15);
+        assert_eq!(flow, plain.edmonds_karp(0, 15));
+        assert_eq!(stats.augmenting_paths(), flow.1);
+        // shortest augmenting paths never get shorter and need at least the 4 bits flipped
+        assert!(stats.path_lengths.windows(2).all(|pair| pair[0] <= pair[1]));
+        assert!(stats.path_lengths.iter().all(|length| *length >= 4));
+        assert_eq!(stats.phases, flow.1 + 1);
+        assert!(stats.pops <= stats.pushes && stats.scanned_arcs > 0);
+
+        let (dinic_flow, dinic_stats) = dinic.dinic_stats::<FlowStats>(0, 15);
+        assert_eq!(dinic_flow.0, flow.0);
+        assert_eq!(dinic_stats.augmenting_paths(), dinic_flow.1);
+        assert!(dinic_stats.path_lengths.windows(2).all(|pair| pair[0] <= pair[1]));
+        assert!(dinic_stats.longest_path() >= 4 && dinic_stats.mean_path_length() >= 4.0);
+    }
+
     #[test]
     fn bigraph_test() {
         Bigraph::new(6, 4);
@@ -370,4 +492,14 @@ mod tests {
         let bigraph = Bigraph::new(6, 4);
         bigraph.hopcroft_karp();
     }
+
+    #[test]
+    fn hopcroft_karp_stats_test() {
+        let bigraph = Bigraph::new(5, 3);
+        let (matching, stats) = bigraph.hopcroft_karp_stats::<FlowStats>();
+        assert_eq!(matching, bigraph.hopcroft_karp());
+        assert_eq!(stats.augmenting_paths(), matching);
+        assert!(stats.path_lengths.iter().all(|length| length % 2 == 1));
+        assert_eq!(stats.path_lengths.first(), Some(&1));
+    }
 }